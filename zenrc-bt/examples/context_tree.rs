@@ -0,0 +1,53 @@
+use zenrc_bt::Status;
+use zenrc_bt::context::{Action, Condition, ContextNode, Sequence};
+
+/// 行为树操作的世界状态，节点直接以字段访问，无需黑板
+struct World {
+    position: i32,
+    target: i32,
+    door_open: bool,
+}
+
+/// 每次 tick 向目标移动一格，未到达时返回 Running
+struct MoveTo {
+    status: Status,
+}
+
+impl ContextNode<World> for MoveTo {
+    fn get_status(&self) -> Status {
+        self.status
+    }
+    fn set_status(&mut self, s: Status) {
+        self.status = s;
+    }
+
+    fn update(&mut self, world: &mut World) -> Status {
+        if world.position == world.target {
+            return Status::Success;
+        }
+        world.position += (world.target - world.position).signum();
+        println!("MoveTo: position = {}", world.position);
+        Status::Running
+    }
+}
+
+fn main() {
+    // 创建行为树：Sequence( 门已打开?, MoveTo, 关门 )
+    let mut root = Sequence::new(vec![
+        Box::new(Condition::new(|world: &World| world.door_open)),
+        Box::new(MoveTo { status: Status::Invalid }),
+        Box::new(Action::new(|world: &mut World| {
+            world.door_open = false;
+            println!("close the door");
+            Status::Success
+        })),
+    ]);
+
+    let mut world = World { position: 0, target: 3, door_open: true };
+    // 执行 tick 直到结束
+    let mut status = Status::Running;
+    while status == Status::Running {
+        status = root.tick(&mut world);
+    }
+    println!("Root status = {:?}, door_open = {}", status, world.door_open);
+}
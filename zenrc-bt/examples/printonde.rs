@@ -31,24 +31,22 @@ impl Node for PrintNode {
 
     fn update(&mut self) -> Status {
         println!("PrintNode says: {}", self.msg);
-        self.get_blackboard().unwrap().borrow_mut().insert("last_message".to_string(), Box::new("sdsdsd".to_string()));
-        self.get_blackboard().unwrap().borrow_mut().insert("tow_message".to_string(), Box::new("zxczxc"));
+        let bb = self.blackboard_or_default();
+        bb.borrow_mut().insert("last_message".to_string(), Box::new("sdsdsd".to_string()));
+        bb.borrow_mut().insert("tow_message".to_string(), Box::new("zxczxc"));
         Status::Success
     }
 }
 
 fn main() {
-    // 创建一个黑板
-    let bb: BlackboardPtr = BlackboardPtr::new();
-
-    // 创建行为树：Sequence( Print("Hello"), Print("World") )
+    // 创建行为树：Sequence( Print("Hello"), Print("World") )，黑板由 BehaviorTree 创建并设置
     let mut root = Sequence::new(Vec::new());
     root.add_child(Box::new(PrintNode::new("Hello")));
     root.add_child(Box::new(PrintNode::new("World")));
-    root.set_blackboard(bb);
+    let mut tree = BehaviorTree::new(Box::new(root));
     // 执行 tick
-    let status = root.tick();
-    println!("bb = {:?}", root.get_blackboard().unwrap().get::<String>("last_message").unwrap());
-    println!("bb = {:?}", root.get_blackboard().unwrap().get::<&str>("tow_message").unwrap());
+    let status = tree.tick();
+    println!("bb = {:?}", tree.blackboard().get::<String>("last_message").unwrap());
+    println!("bb = {:?}", tree.blackboard().get::<&str>("tow_message").unwrap());
     println!("Root status = {:?}", status);
-}
\ No newline at end of file
+}
@@ -0,0 +1,211 @@
+//! 常用动作（叶子）节点
+use std::cell::RefCell;
+use std::future::Future;
+use std::pin::Pin;
+use std::rc::Rc;
+use std::task::{Context, Poll, Waker};
+use std::time::{Duration, Instant};
+
+use crate::{BaseNode, BlackboardPtr, Node, Status};
+
+/// 等待节点：按墙钟计时，进入后 duration 内返回 Running，之后返回 Success
+///
+/// 每次重新进入（initialize）都会重新计时。
+pub struct Wait {
+    base: BaseNode,
+    duration: Duration,
+    started: Instant,
+}
+impl Wait {
+    pub fn new(duration: Duration) -> Self {
+        Self {
+            base: BaseNode::new(),
+            duration,
+            started: Instant::now(),
+        }
+    }
+}
+impl Node for Wait {
+    fn get_blackboard(&self) -> Option<BlackboardPtr> {
+        self.base.get_blackboard()
+    }
+    fn set_blackboard(&mut self, bb: BlackboardPtr) {
+        self.base.set_blackboard(bb);
+    }
+    fn get_status(&self) -> Status {
+        self.base.get_status()
+    }
+    fn set_status(&mut self, s: Status) {
+        self.base.set_status(s);
+    }
+    fn clone_node(&self) -> Box<dyn Node> {
+        Box::new(Wait::new(self.duration))
+    }
+    fn initialize(&mut self) {
+        self.started = Instant::now();
+    }
+    fn update(&mut self) -> Status {
+        if self.started.elapsed() >= self.duration {
+            Status::Success
+        } else {
+            Status::Running
+        }
+    }
+}
+
+/// 以 async 方式实现的叶子节点，适合网络请求、文件读取等 IO 操作，通过 [`AsyncLeaf`] 放入行为树
+// 行为树在单线程中 tick，返回的 future 不需要 Send
+#[allow(async_fn_in_trait)]
+pub trait AsyncNode {
+    /// 执行一次操作，完成时返回节点状态
+    async fn update(&mut self) -> Status;
+
+    /// 深拷贝节点，供 [`AsyncLeaf`] 的 `clone_node` 使用，与 [`Node::clone_node`] 一样需要自行实现
+    ///
+    /// 默认实现直接 panic。
+    fn clone_node(&self) -> Self
+    where
+        Self: Sized,
+    {
+        panic!("{} does not implement clone_node", std::any::type_name::<Self>())
+    }
+}
+
+/// 把 [`AsyncNode`] 适配为普通节点：每次 tick 轮询一次 future，未完成时返回 Running
+///
+/// 使用 no-op waker 轮询，不依赖异步运行时，future 是否就绪只在下一次 tick 时检查；
+/// 因此只适用于由 tick 推动（或在其他线程完成、本线程轮询结果）的 future。
+/// future 完成后下一次 tick 重新调用 `update()`；halt() 丢弃未完成的 future，即取消该操作。
+/// `clone_node` 通过 [`AsyncNode::clone_node`] 复制节点，操作进行中（Running）时不能克隆。
+pub struct AsyncLeaf<N: AsyncNode + 'static> {
+    base: BaseNode,
+    node: Rc<RefCell<N>>,
+    future: Option<Pin<Box<dyn Future<Output = Status>>>>,
+}
+impl<N: AsyncNode + 'static> AsyncLeaf<N> {
+    pub fn new(node: N) -> Self {
+        Self {
+            base: BaseNode::new(),
+            node: Rc::new(RefCell::new(node)),
+            future: None,
+        }
+    }
+}
+impl<N: AsyncNode + 'static> Node for AsyncLeaf<N> {
+    fn get_blackboard(&self) -> Option<BlackboardPtr> {
+        self.base.get_blackboard()
+    }
+    fn set_blackboard(&mut self, bb: BlackboardPtr) {
+        self.base.set_blackboard(bb);
+    }
+    fn get_status(&self) -> Status {
+        self.base.get_status()
+    }
+    fn set_status(&mut self, s: Status) {
+        self.base.set_status(s);
+    }
+    fn clone_node(&self) -> Box<dyn Node> {
+        let node = self.node.try_borrow().expect("cannot clone an AsyncLeaf while it is running");
+        Box::new(AsyncLeaf::new(node.clone_node()))
+    }
+    fn halt(&mut self) {
+        self.future = None;
+        self.interrupt();
+        self.reset();
+    }
+    // 同一时刻最多只有一个 future 借用节点，跨 await 持有的 RefCell 借用不会冲突
+    #[allow(clippy::await_holding_refcell_ref)]
+    fn update(&mut self) -> Status {
+        // future 持有节点的共享引用，不借用 self，可以跨 tick 保存
+        let future = self.future.get_or_insert_with(|| {
+            let node = self.node.clone();
+            Box::pin(async move { node.borrow_mut().update().await })
+        });
+        match future.as_mut().poll(&mut Context::from_waker(Waker::noop())) {
+            Poll::Ready(status) => {
+                self.future = None;
+                status
+            }
+            Poll::Pending => Status::Running,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cell::Cell;
+
+    // 前 n 次轮询返回 Pending 的 future
+    struct PendingFor(usize);
+
+    impl Future for PendingFor {
+        type Output = ();
+        fn poll(mut self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<()> {
+            if self.0 == 0 {
+                return Poll::Ready(());
+            }
+            self.0 -= 1;
+            Poll::Pending
+        }
+    }
+
+    // 模拟一次需要轮询三次的请求，完成次数通过 done 观察
+    struct Fetch {
+        done: Rc<Cell<usize>>,
+    }
+
+    impl AsyncNode for Fetch {
+        async fn update(&mut self) -> Status {
+            PendingFor(3).await;
+            self.done.set(self.done.get() + 1);
+            Status::Success
+        }
+
+        fn clone_node(&self) -> Self {
+            Fetch { done: self.done.clone() }
+        }
+    }
+
+    #[test]
+    fn wait_succeeds_after_duration_and_rearms() {
+        let mut node = Wait::new(Duration::from_millis(20));
+
+        assert_eq!(node.tick(), Status::Running);
+        std::thread::sleep(Duration::from_millis(25));
+        assert_eq!(node.tick(), Status::Success);
+
+        // 结束后重新进入会重新计时
+        assert_eq!(node.tick(), Status::Running);
+    }
+
+    #[test]
+    fn async_leaf_runs_until_future_resolves() {
+        let done = Rc::new(Cell::new(0));
+        let mut node = AsyncLeaf::new(Fetch { done: done.clone() });
+
+        for _ in 0..3 {
+            assert_eq!(node.tick(), Status::Running);
+        }
+        assert_eq!(node.tick(), Status::Success);
+        assert_eq!(done.get(), 1);
+
+        // halt 取消进行中的请求，下次 tick 重新开始
+        assert_eq!(node.tick(), Status::Running);
+        node.halt();
+        for _ in 0..3 {
+            assert_eq!(node.tick(), Status::Running);
+        }
+        assert_eq!(node.tick(), Status::Success);
+        assert_eq!(done.get(), 2);
+
+        // 副本有自己的 future，不受原节点影响
+        let mut copy = node.clone_node();
+        assert_eq!(node.tick(), Status::Running);
+        for _ in 0..3 {
+            assert_eq!(copy.tick(), Status::Running);
+        }
+        assert_eq!(copy.tick(), Status::Success);
+        assert_eq!(done.get(), 3);
+    }
+}
@@ -0,0 +1,336 @@
+//! 黑板：节点间以字符串为键共享任意类型数据，依赖 std，仅在启用 `std` feature 时可用
+use std::any::{Any, TypeId};
+use std::cell::{BorrowError, BorrowMutError, Cell, Ref, RefCell, RefMut};
+use std::collections::HashMap;
+use std::ops::Deref;
+use std::rc::Rc;
+use std::sync::Arc;
+
+type Map = HashMap<String, Box<dyn Any>>;
+
+/// 黑板本地的条目，通过 [`BlackboardPtr`] 的 Deref 访问，用法与 `RefCell<HashMap>` 相同
+///
+/// 无法得知一次可变借用改动了哪些键，因此每次 [`borrow_mut`](Self::borrow_mut)
+/// 都会使本黑板所有键的版本号加一，见 [`BlackboardPtr::version`]。
+pub struct BlackboardEntries {
+    cell: RefCell<Map>,
+    // 通过 borrow_mut 可变借用的次数
+    raw_writes: Cell<u64>,
+}
+
+impl BlackboardEntries {
+    pub fn borrow(&self) -> Ref<'_, Map> {
+        self.cell.borrow()
+    }
+
+    pub fn try_borrow(&self) -> Result<Ref<'_, Map>, BorrowError> {
+        self.cell.try_borrow()
+    }
+
+    pub fn borrow_mut(&self) -> RefMut<'_, Map> {
+        let map = self.cell.borrow_mut();
+        self.raw_writes.set(self.raw_writes.get() + 1);
+        map
+    }
+
+    pub fn try_borrow_mut(&self) -> Result<RefMut<'_, Map>, BorrowMutError> {
+        let map = self.cell.try_borrow_mut()?;
+        self.raw_writes.set(self.raw_writes.get() + 1);
+        Ok(map)
+    }
+}
+
+// box<dyn Any> 可以存储任何类型的数据
+// 通过 downcast_ref::<Type>() 来获取具体类型的引用
+/// 行为树的黑板句柄，clone 得到的句柄共享同一份数据
+///
+/// # 线程
+///
+/// 黑板只在单线程内使用：内部借用检查基于 `RefCell`，链接的键使用 `Rc`，
+/// 因此既不是 `Send` 也不是 `Sync`，编译器会拒绝把它移动或共享到其他线程。
+/// 同一线程内的多棵树可以 clone 同一个黑板（或 [`linked_view`](Self::linked_view)）共享数据；
+/// 需要跨线程时，每个线程持有自己的树和黑板，通过 channel 等方式在线程间传递值。
+///
+/// ```compile_fail
+/// let bb = zenrc_bt::BlackboardPtr::new();
+/// std::thread::spawn(move || bb.set("speed", 1.0));
+/// ```
+///
+/// debug 构建下，若通过 `unsafe impl Send` 的包装类型绕过上述限制，
+/// 在创建线程之外访问黑板会触发断言。
+#[derive(Clone)]
+pub struct BlackboardPtr {
+    map: Arc<BlackboardEntries>,
+    // 代理到父黑板的键
+    links: Rc<HashMap<String, BlackboardPtr>>,
+    // 每个键经 set 等接口写入的次数，见 version
+    versions: Rc<RefCell<HashMap<String, u64>>>,
+    // 创建黑板的线程，只用于 debug 断言
+    #[cfg(debug_assertions)]
+    thread: std::thread::ThreadId,
+}
+
+impl BlackboardPtr {
+    pub fn new() -> Self {
+        BlackboardPtr {
+            map: Arc::new(BlackboardEntries {
+                cell: RefCell::new(HashMap::new()),
+                raw_writes: Cell::new(0),
+            }),
+            links: Rc::default(),
+            versions: Rc::default(),
+            #[cfg(debug_assertions)]
+            thread: std::thread::current().id(),
+        }
+    }
+
+    // 检查当前线程是否为创建黑板的线程，见类型文档中的“线程”一节
+    fn check_thread(&self) {
+        #[cfg(debug_assertions)]
+        assert_eq!(
+            self.thread,
+            std::thread::current().id(),
+            "BlackboardPtr accessed from a thread other than the one that created it"
+        );
+    }
+
+    /// 创建一个新黑板，keys 中的键读写都代理到本黑板，其余键只存在于新黑板本地
+    ///
+    /// 用于多棵行为树之间有控制地共享部分数据：每棵树使用各自的视图，
+    /// 只有列出的键互相可见。视图也可以再创建视图，链接的键最终落到最初的黑板上。
+    ///
+    /// 链接只对 [`get`](Self::get)、[`try_get`](Self::try_get)、[`set`](Self::set)、
+    /// [`entry_or_insert_with`](Self::entry_or_insert_with) 等按键访问的接口生效。
+    /// 通过 Deref 得到的 map 只是视图本地的条目：`borrow_mut().insert(..)` 写入链接的键时
+    /// 只落在视图本地，父黑板和其他视图都看不到，读取也看不到父黑板中的值。
+    pub fn linked_view(&self, keys: &[&str]) -> BlackboardPtr {
+        let links = keys.iter().map(|key| (key.to_string(), self.clone())).collect();
+        BlackboardPtr {
+            links: Rc::new(links),
+            ..BlackboardPtr::new()
+        }
+    }
+
+    // key 实际所在的黑板：链接的键沿父黑板向上查找
+    fn owner(&self, key: &str) -> &BlackboardPtr {
+        self.check_thread();
+        match self.links.get(key) {
+            Some(parent) => parent.owner(key),
+            None => self,
+        }
+    }
+
+    /// 写入 key，覆盖旧值（无论旧值类型）；key 是链接的键时写入父黑板
+    pub fn set<T: 'static>(&self, key: &str, value: T) {
+        let owner = self.owner(key);
+        owner.map.cell.borrow_mut().insert(key.to_string(), Box::new(value));
+        owner.touch(key);
+    }
+
+    /// key 的版本号：每次通过 [`set`](Self::set) 或 [`entry_or_insert_with`](Self::entry_or_insert_with)
+    /// 写入时增大，从未写入时为 0；用于判断键是否发生了变化
+    ///
+    /// 通过 Deref 可变借用 map（如 `borrow_mut().insert(..)`）时，所在黑板的所有键的版本号都会增大。
+    pub fn version(&self, key: &str) -> u64 {
+        let owner = self.owner(key);
+        let typed = owner.versions.borrow().get(key).copied().unwrap_or(0);
+        typed + owner.map.raw_writes.get()
+    }
+
+    fn touch(&self, key: &str) {
+        *self.versions.borrow_mut().entry(key.to_string()).or_default() += 1;
+    }
+
+    // key 当前值的类型，用于端口校验
+    pub(crate) fn type_id_of(&self, key: &str) -> Option<TypeId> {
+        self.owner(key).map.cell.borrow().get(key).map(|value| (**value).type_id())
+    }
+
+    /// 读取 key 对应的 T 类型值，不存在或类型不符时返回 None
+    ///
+    /// # Panics
+    ///
+    /// 黑板正被可变借用（如持有 `borrow_mut()`）时 panic，需要处理借用冲突时使用 [`try_get`]。
+    ///
+    /// [`try_get`]: BlackboardPtr::try_get
+    pub fn get<'a, T: 'static>(&'a self, key: &str) -> Option<Ref<'a, T>> {
+         Ref::filter_map(self.owner(key).map.cell.borrow(), |map| {
+            map.get(key)?.downcast_ref::<T>()
+        })
+        .ok()
+    }
+
+    /// 与 [`get`](BlackboardPtr::get) 相同，但黑板正被可变借用时返回借用错误而不是 panic
+    pub fn try_get<T: 'static>(&self, key: &str) -> Result<Option<Ref<'_, T>>, BorrowError> {
+        let map = self.owner(key).map.cell.try_borrow()?;
+        Ok(Ref::filter_map(map, |map| map.get(key)?.downcast_ref::<T>()).ok())
+    }
+
+    /// 读取 i64 值的副本，不存在或类型不符时返回 None
+    pub fn get_i64(&self, key: &str) -> Option<i64> {
+        self.get::<i64>(key).map(|value| *value)
+    }
+
+    /// 读取 f64 值的副本，不存在或类型不符时返回 None
+    pub fn get_f64(&self, key: &str) -> Option<f64> {
+        self.get::<f64>(key).map(|value| *value)
+    }
+
+    /// 读取 bool 值的副本，不存在或类型不符时返回 None
+    pub fn get_bool(&self, key: &str) -> Option<bool> {
+        self.get::<bool>(key).map(|value| *value)
+    }
+
+    /// 读取 String 值的副本，不存在或类型不符时返回 None
+    pub fn get_string(&self, key: &str) -> Option<String> {
+        self.get::<String>(key).map(|value| value.clone())
+    }
+
+    /// 获取 key 对应值的可变引用，不存在时先用 default() 的结果插入
+    ///
+    /// 注意：若 key 已存在但类型不是 T，旧值会被 default() 的结果**覆盖**。
+    /// default() 在黑板未被借用时调用，闭包内可以安全地读取黑板。
+    /// 返回的引用可能被修改，因此每次调用都算作一次写入（版本号加一）。
+    pub fn entry_or_insert_with<T: 'static>(
+        &self,
+        key: &str,
+        default: impl FnOnce() -> T,
+    ) -> RefMut<'_, T> {
+        let owner = self.owner(key);
+        let present = owner.map.cell.borrow().get(key).is_some_and(|value| value.is::<T>());
+        if !present {
+            let value = default();
+            owner.map.cell.borrow_mut().insert(key.to_string(), Box::new(value));
+        }
+        owner.touch(key);
+        RefMut::map(owner.map.cell.borrow_mut(), |map| {
+            map.get_mut(key)
+                .and_then(|value| value.downcast_mut::<T>())
+                .expect("value was just inserted")
+        })
+    }
+}
+
+impl Deref for BlackboardPtr {
+    type Target = BlackboardEntries;
+
+    fn deref(&self) -> &Self::Target {
+        self.check_thread();
+        &self.map
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn entry_or_insert_with_runs_default_once() {
+        let bb = BlackboardPtr::new();
+        let mut calls = 0;
+        for _ in 0..2 {
+            *bb.entry_or_insert_with("count", || {
+                calls += 1;
+                0i32
+            }) += 1;
+        }
+        assert_eq!(calls, 1);
+        assert_eq!(*bb.get::<i32>("count").unwrap(), 2);
+
+        // 类型不匹配时覆盖旧值
+        bb.borrow_mut().insert("name".to_string(), Box::new(1u8));
+        assert_eq!(*bb.entry_or_insert_with("name", || "bt".to_string()), "bt");
+    }
+
+    #[test]
+    fn scalar_getters_copy_values() {
+        let bb = BlackboardPtr::new();
+        bb.borrow_mut().insert("count".to_string(), Box::new(3_i64));
+        bb.borrow_mut().insert("speed".to_string(), Box::new(0.5_f64));
+        bb.borrow_mut().insert("armed".to_string(), Box::new(true));
+        bb.borrow_mut().insert("mode".to_string(), Box::new("auto".to_string()));
+
+        assert_eq!(bb.get_i64("count"), Some(3));
+        assert_eq!(bb.get_f64("speed"), Some(0.5));
+        assert_eq!(bb.get_bool("armed"), Some(true));
+        assert_eq!(bb.get_string("mode"), Some("auto".to_string()));
+
+        // 类型不符或不存在时返回 None
+        assert_eq!(bb.get_i64("speed"), None);
+        assert_eq!(bb.get_f64("count"), None);
+        assert_eq!(bb.get_bool("mode"), None);
+        assert_eq!(bb.get_string("armed"), None);
+        assert_eq!(bb.get_i64("missing"), None);
+    }
+
+    #[test]
+    fn try_get_reports_conflicting_borrow() {
+        let bb = BlackboardPtr::new();
+        bb.borrow_mut().insert("count".to_string(), Box::new(1_i32));
+
+        let guard = bb.borrow_mut();
+        assert!(bb.try_get::<i32>("count").is_err());
+        drop(guard);
+
+        assert_eq!(*bb.try_get::<i32>("count").unwrap().unwrap(), 1);
+        assert!(bb.try_get::<i32>("missing").unwrap().is_none());
+    }
+
+    #[test]
+    fn linked_view_shares_only_listed_keys() {
+        let shared = BlackboardPtr::new();
+        let nav = shared.linked_view(&["target"]);
+        let arm = shared.linked_view(&["target"]);
+
+        nav.set("target", 7_i32);
+        nav.set("speed", 1.5_f64);
+        assert_eq!(*arm.get::<i32>("target").unwrap(), 7);
+        assert_eq!(*shared.get::<i32>("target").unwrap(), 7);
+        // 未链接的键只留在本地
+        assert_eq!(nav.get_f64("speed"), Some(1.5));
+        assert_eq!(arm.get_f64("speed"), None);
+        assert!(!shared.borrow().contains_key("speed"));
+
+        *arm.entry_or_insert_with("target", || 0_i32) += 1;
+        assert_eq!(*nav.get::<i32>("target").unwrap(), 8);
+    }
+
+    #[test]
+    fn linked_view_deref_writes_stay_local() {
+        let shared = BlackboardPtr::new();
+        let nav = shared.linked_view(&["target"]);
+        shared.set("target", 7_i32);
+
+        // 绕过类型化接口直接写 map：只写入视图本地，父黑板不受影响
+        nav.borrow_mut().insert("target".to_string(), Box::new(9_i32));
+        assert_eq!(*shared.get::<i32>("target").unwrap(), 7);
+        assert_eq!(*nav.get::<i32>("target").unwrap(), 7);
+        assert!(nav.borrow().contains_key("target"));
+    }
+
+    #[test]
+    fn clones_share_data_within_one_thread() {
+        // 预期用法：同一线程内的多个句柄（多棵树）共享同一份数据
+        let bb = BlackboardPtr::new();
+        let other = bb.clone();
+        bb.set("speed", 1.5_f64);
+        assert_eq!(other.get_f64("speed"), Some(1.5));
+    }
+
+    #[cfg(debug_assertions)]
+    #[test]
+    fn cross_thread_access_is_caught_in_debug_builds() {
+        struct Smuggled(BlackboardPtr);
+        // SAFETY: 仅用于测试断言；唯一的句柄被整体移入新线程，
+        // Rc 计数和 RefCell 不会被两个线程同时访问
+        unsafe impl Send for Smuggled {}
+
+        let smuggled = Smuggled(BlackboardPtr::new());
+        let result = std::thread::spawn(move || {
+            let smuggled = smuggled;
+            smuggled.0.get_i64("speed")
+        })
+        .join();
+        assert!(result.is_err());
+    }
+}
@@ -0,0 +1,375 @@
+//! 从 TOML 配置文件构建行为树
+//!
+//! 配置中的每个节点是一个表：`type` 为在 [`NodeRegistry`] 中注册的类型名，
+//! 可选的 `params` 表为参数，复合节点用 `children` 数组、装饰节点用 `child` 表描述子节点。
+//! 根节点位于 `[root]` 表：
+//!
+//! ```toml
+//! [root]
+//! type = "sequence"
+//!
+//! [[root.children]]
+//! type = "max_ticks"
+//! params = { max_ticks = 10, on_timeout = "failure" }
+//! child = { type = "patrol", params = { waypoints = 3 } }
+//! ```
+//!
+//! 出错时 [`ConfigError`] 带有出错节点在配置中的路径，如 `root.children[0].child`。
+use std::collections::HashMap;
+use std::time::Duration;
+
+use thiserror::Error;
+use toml::{Table, Value};
+
+use crate::decorator::{
+    Cooldown, ForceResult, MaxTicks, Measured, Once, RepeatUntilFailure, Throttle,
+};
+use crate::{
+    Node, ParallelAll, Selector, Sequence, StatefulSelector, StatefulSequence, Status,
+    StickySelector, Switch,
+};
+
+#[derive(Debug, Error)]
+pub enum ConfigError {
+    #[error("invalid TOML: {0}")]
+    Parse(#[from] toml::de::Error),
+    #[error("{path}: unknown node type `{node_type}`")]
+    UnknownType { path: String, node_type: String },
+    #[error("{path}: missing required param `{name}`")]
+    MissingParam { path: String, name: String },
+    #[error("{path}: param `{name}` must be {expected}")]
+    InvalidParam {
+        path: String,
+        name: String,
+        expected: &'static str,
+    },
+    #[error("{path}: {message}")]
+    Invalid { path: String, message: String },
+}
+
+/// 传给节点工厂的节点描述：参数和已构建好的子节点
+pub struct NodeSpec {
+    path: String,
+    params: Table,
+    children: Vec<Box<dyn Node>>,
+}
+
+impl NodeSpec {
+    /// 节点在配置中的路径，用于自定义错误信息
+    pub fn path(&self) -> &str {
+        &self.path
+    }
+
+    fn param(&self, name: &str) -> Result<&Value, ConfigError> {
+        self.params.get(name).ok_or_else(|| ConfigError::MissingParam {
+            path: self.path.clone(),
+            name: name.to_string(),
+        })
+    }
+
+    fn invalid_param(&self, name: &str, expected: &'static str) -> ConfigError {
+        ConfigError::InvalidParam {
+            path: self.path.clone(),
+            name: name.to_string(),
+            expected,
+        }
+    }
+
+    /// 是否提供了参数 name
+    pub fn has_param(&self, name: &str) -> bool {
+        self.params.contains_key(name)
+    }
+
+    /// 读取字符串参数
+    pub fn str(&self, name: &str) -> Result<&str, ConfigError> {
+        self.param(name)?.as_str().ok_or_else(|| self.invalid_param(name, "a string"))
+    }
+
+    /// 读取整数参数
+    pub fn i64(&self, name: &str) -> Result<i64, ConfigError> {
+        self.param(name)?.as_integer().ok_or_else(|| self.invalid_param(name, "an integer"))
+    }
+
+    /// 读取非负整数参数
+    pub fn usize(&self, name: &str) -> Result<usize, ConfigError> {
+        usize::try_from(self.i64(name)?)
+            .map_err(|_| self.invalid_param(name, "a non-negative integer"))
+    }
+
+    /// 读取浮点参数，整数会被转换为浮点
+    pub fn f64(&self, name: &str) -> Result<f64, ConfigError> {
+        match self.param(name)? {
+            Value::Float(value) => Ok(*value),
+            Value::Integer(value) => Ok(*value as f64),
+            _ => Err(self.invalid_param(name, "a number")),
+        }
+    }
+
+    /// 读取布尔参数
+    pub fn bool(&self, name: &str) -> Result<bool, ConfigError> {
+        self.param(name)?.as_bool().ok_or_else(|| self.invalid_param(name, "a boolean"))
+    }
+
+    /// 读取终止状态参数，取值为 `"success"` 或 `"failure"`
+    pub fn status(&self, name: &str) -> Result<Status, ConfigError> {
+        match self.str(name)? {
+            "success" => Ok(Status::Success),
+            "failure" => Ok(Status::Failure),
+            _ => Err(self.invalid_param(name, "\"success\" or \"failure\"")),
+        }
+    }
+
+    /// 取出全部子节点（复合节点使用）
+    pub fn take_children(&mut self) -> Vec<Box<dyn Node>> {
+        std::mem::take(&mut self.children)
+    }
+
+    /// 取出唯一的子节点（装饰节点使用），子节点数量不是 1 时返回错误
+    pub fn take_child(&mut self) -> Result<Box<dyn Node>, ConfigError> {
+        if self.children.len() != 1 {
+            return Err(ConfigError::Invalid {
+                path: self.path.clone(),
+                message: format!("expected exactly one child, found {}", self.children.len()),
+            });
+        }
+        Ok(self.children.remove(0))
+    }
+}
+
+/// 节点工厂：根据节点描述创建节点
+pub type NodeFactory = Box<dyn Fn(NodeSpec) -> Result<Box<dyn Node>, ConfigError>>;
+
+/// 节点类型注册表，按类型名查找工厂构建整棵树
+///
+/// [`new`](NodeRegistry::new) 预先注册了内置的复合节点（`sequence`、`selector`、
+/// `stateful_sequence`、`stateful_selector`、`sticky_selector`、`parallel_all`、`switch`）和装饰节点
+/// （`max_ticks`、`once`、`repeat_until_failure`、`force_result`、`cooldown`、`measured`、
+/// `throttle`），
+/// 叶子节点由使用者通过 [`register`](NodeRegistry::register) 注册。
+pub struct NodeRegistry {
+    factories: HashMap<String, NodeFactory>,
+}
+
+impl Default for NodeRegistry {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl NodeRegistry {
+    pub fn new() -> Self {
+        let mut registry = Self {
+            factories: HashMap::new(),
+        };
+        registry.register("sequence", |mut spec| Ok(Box::new(Sequence::new(spec.take_children()))));
+        registry.register("selector", |mut spec| Ok(Box::new(Selector::new(spec.take_children()))));
+        registry.register("stateful_sequence", |mut spec| {
+            Ok(Box::new(StatefulSequence::new(spec.take_children())))
+        });
+        registry.register("stateful_selector", |mut spec| {
+            Ok(Box::new(StatefulSelector::new(spec.take_children())))
+        });
+        registry.register("sticky_selector", |mut spec| {
+            Ok(Box::new(StickySelector::new(spec.take_children())))
+        });
+        registry.register("parallel_all", |mut spec| {
+            Ok(Box::new(ParallelAll::new(spec.take_children())))
+        });
+        registry.register("switch", |mut spec| {
+            let key = spec.str("key")?.to_string();
+            Ok(Box::new(Switch::new(&key, spec.take_children())))
+        });
+        registry.register("max_ticks", |mut spec| {
+            let max_ticks = spec.usize("max_ticks")?;
+            let on_timeout = spec.status("on_timeout")?;
+            Ok(Box::new(MaxTicks::new(spec.take_child()?, max_ticks, on_timeout)))
+        });
+        registry.register("once", |mut spec| Ok(Box::new(Once::new(spec.take_child()?))));
+        registry.register("repeat_until_failure", |mut spec| {
+            let max = if spec.has_param("max") { spec.usize("max")? } else { 0 };
+            Ok(Box::new(RepeatUntilFailure::new(spec.take_child()?, max)))
+        });
+        registry.register("force_result", |mut spec| {
+            let running_maps_to = spec.status("running_maps_to")?;
+            Ok(Box::new(ForceResult::new(spec.take_child()?, running_maps_to)))
+        });
+        registry.register("cooldown", |mut spec| {
+            let seconds = spec.f64("seconds")?;
+            let cooldown = Duration::try_from_secs_f64(seconds)
+                .map_err(|_| spec.invalid_param("seconds", "a non-negative duration"))?;
+            Ok(Box::new(Cooldown::new(spec.take_child()?, cooldown)))
+        });
+        registry.register("measured", |mut spec| {
+            let key = spec.str("key")?.to_string();
+            Ok(Box::new(Measured::new(spec.take_child()?, &key)))
+        });
+        registry.register("throttle", |mut spec| {
+            let every_n_ticks = spec.usize("every_n_ticks")?;
+            if every_n_ticks == 0 {
+                return Err(spec.invalid_param("every_n_ticks", "a positive integer"));
+            }
+            Ok(Box::new(Throttle::new(spec.take_child()?, every_n_ticks)))
+        });
+        registry
+    }
+
+    /// 注册类型名 name 的工厂，同名的已有工厂（包括内置节点）会被替换
+    pub fn register(
+        &mut self,
+        name: &str,
+        factory: impl Fn(NodeSpec) -> Result<Box<dyn Node>, ConfigError> + 'static,
+    ) {
+        self.factories.insert(name.to_string(), Box::new(factory));
+    }
+
+    /// 解析 TOML 文本，构建 `[root]` 表描述的树，返回根节点
+    pub fn build_toml(&self, source: &str) -> Result<Box<dyn Node>, ConfigError> {
+        let table: Table = source.parse()?;
+        let root = table.get("root").ok_or_else(|| ConfigError::Invalid {
+            path: String::new(),
+            message: "missing [root] table".to_string(),
+        })?;
+        self.build(root, "root".to_string())
+    }
+
+    fn build(&self, value: &Value, path: String) -> Result<Box<dyn Node>, ConfigError> {
+        let invalid = |path: &str, message: &str| ConfigError::Invalid {
+            path: path.to_string(),
+            message: message.to_string(),
+        };
+        let table = value.as_table().ok_or_else(|| invalid(&path, "node must be a table"))?;
+        let node_type = table
+            .get("type")
+            .and_then(Value::as_str)
+            .ok_or_else(|| invalid(&path, "missing string field `type`"))?;
+        let factory = self.factories.get(node_type).ok_or_else(|| ConfigError::UnknownType {
+            path: path.clone(),
+            node_type: node_type.to_string(),
+        })?;
+        let params = match table.get("params") {
+            Some(params) => params
+                .as_table()
+                .cloned()
+                .ok_or_else(|| invalid(&path, "`params` must be a table"))?,
+            None => Table::new(),
+        };
+
+        let mut children = Vec::new();
+        if let Some(child) = table.get("child") {
+            children.push(self.build(child, format!("{}.child", path))?);
+        }
+        if let Some(list) = table.get("children") {
+            let list = list
+                .as_array()
+                .ok_or_else(|| invalid(&path, "`children` must be an array of tables"))?;
+            for (i, child) in list.iter().enumerate() {
+                children.push(self.build(child, format!("{}.children[{}]", path, i))?);
+            }
+        }
+
+        factory(NodeSpec {
+            path,
+            params,
+            children,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::BaseNode;
+    use crate::BlackboardPtr;
+
+    // 每次 tick 前进一个航点，走完 waypoints 个后成功
+    struct Patrol {
+        base: BaseNode,
+        waypoints: i64,
+        visited: i64,
+    }
+
+    impl Node for Patrol {
+        fn get_blackboard(&self) -> Option<BlackboardPtr> {
+            self.base.get_blackboard()
+        }
+        fn set_blackboard(&mut self, bb: BlackboardPtr) {
+            self.base.set_blackboard(bb);
+        }
+        fn get_status(&self) -> Status {
+            self.base.get_status()
+        }
+        fn set_status(&mut self, s: Status) {
+            self.base.set_status(s);
+        }
+        fn initialize(&mut self) {
+            self.visited = 0;
+        }
+        fn update(&mut self) -> Status {
+            self.visited += 1;
+            if self.visited >= self.waypoints { Status::Success } else { Status::Running }
+        }
+    }
+
+    fn registry() -> NodeRegistry {
+        let mut registry = NodeRegistry::new();
+        registry.register("patrol", |spec| {
+            Ok(Box::new(Patrol {
+                base: BaseNode::new(),
+                waypoints: spec.i64("waypoints")?,
+                visited: 0,
+            }))
+        });
+        registry
+    }
+
+    #[test]
+    fn builds_and_ticks_parameterized_tree() {
+        let source = r#"
+            [root]
+            type = "sequence"
+
+            [[root.children]]
+            type = "patrol"
+            params = { waypoints = 2 }
+
+            [[root.children]]
+            type = "force_result"
+            params = { running_maps_to = "failure" }
+            child = { type = "patrol", params = { waypoints = 5 } }
+        "#;
+        let mut root = registry().build_toml(source).unwrap();
+
+        assert_eq!(root.tick(), Status::Running);
+        // 第一个 patrol 完成后，第二个被强制为 Failure
+        assert_eq!(root.tick(), Status::Failure);
+        assert_eq!(root.children().len(), 2);
+    }
+
+    #[test]
+    fn errors_name_the_offending_path() {
+        let unknown = r#"
+            [root]
+            type = "sequence"
+            children = [{ type = "patrol", params = { waypoints = 1 } }, { type = "dance" }]
+        "#;
+        let err = registry().build_toml(unknown).err().unwrap();
+        assert_eq!(err.to_string(), "root.children[1]: unknown node type `dance`");
+
+        let missing = r#"
+            [root]
+            type = "once"
+            child = { type = "patrol" }
+        "#;
+        let err = registry().build_toml(missing).err().unwrap();
+        assert_eq!(err.to_string(), "root.child: missing required param `waypoints`");
+
+        let zero = r#"
+            [root]
+            type = "throttle"
+            params = { every_n_ticks = 0 }
+            child = { type = "patrol", params = { waypoints = 1 } }
+        "#;
+        let err = registry().build_toml(zero).err().unwrap();
+        assert_eq!(err.to_string(), "root: param `every_n_ticks` must be a positive integer");
+    }
+}
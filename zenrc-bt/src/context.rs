@@ -0,0 +1,310 @@
+//! 上下文驱动的行为树核心：只依赖 `core` 和 `alloc`，可在 `no_std` 环境下使用
+//!
+//! 节点在 tick 时借用用户提供的上下文 `Ctx`，以普通字段的形式读写数据，
+//! 不需要黑板的 `downcast` 和 `RefCell` 借用检查。
+use alloc::boxed::Box;
+use alloc::vec::Vec;
+
+use crate::Status;
+
+/// 接收上下文的行为树节点 Trait
+pub trait ContextNode<Ctx> {
+    /// 每个节点必须实现 update()
+    fn update(&mut self, ctx: &mut Ctx) -> Status;
+
+    /// 可以覆盖：初始化
+    fn initialize(&mut self, _ctx: &mut Ctx) {}
+    /// 可以覆盖：结束时调用
+    fn terminate(&mut self, _ctx: &mut Ctx) {}
+
+    /// 状态机逻辑：tick
+    fn tick(&mut self, ctx: &mut Ctx) -> Status {
+        if self.get_status() != Status::Running {
+            self.initialize(ctx);
+        }
+
+        let new_status = self.update(ctx);
+        self.set_status(new_status);
+
+        if new_status != Status::Running {
+            self.terminate(ctx);
+        }
+        new_status
+    }
+
+    fn is_running(&self) -> bool {
+        self.get_status() == Status::Running
+    }
+
+    fn reset(&mut self) {
+        self.set_status(Status::Invalid);
+    }
+
+    /// 可以覆盖：中断节点，正在运行时先调用 terminate()，然后重置为 Invalid
+    fn halt(&mut self, ctx: &mut Ctx) {
+        if self.is_running() {
+            self.terminate(ctx);
+        }
+        self.reset();
+    }
+
+    // ------ 内部状态管理接口 ------
+    fn get_status(&self) -> Status;
+    fn set_status(&mut self, s: Status);
+}
+
+/// 以闭包实现的动作叶子节点，闭包的返回值即为节点状态
+pub struct Action<F> {
+    status: Status,
+    action: F,
+}
+
+impl<F> Action<F> {
+    pub fn new<Ctx>(action: F) -> Self
+    where
+        F: FnMut(&mut Ctx) -> Status,
+    {
+        Self { status: Status::Invalid, action }
+    }
+}
+
+impl<Ctx, F: FnMut(&mut Ctx) -> Status> ContextNode<Ctx> for Action<F> {
+    fn get_status(&self) -> Status {
+        self.status
+    }
+    fn set_status(&mut self, s: Status) {
+        self.status = s;
+    }
+    fn update(&mut self, ctx: &mut Ctx) -> Status {
+        (self.action)(ctx)
+    }
+}
+
+/// 以闭包实现的条件叶子节点，只读访问上下文，条件成立时 Success，否则 Failure
+pub struct Condition<F> {
+    status: Status,
+    condition: F,
+}
+
+impl<F> Condition<F> {
+    pub fn new<Ctx>(condition: F) -> Self
+    where
+        F: Fn(&Ctx) -> bool,
+    {
+        Self { status: Status::Invalid, condition }
+    }
+}
+
+impl<Ctx, F: Fn(&Ctx) -> bool> ContextNode<Ctx> for Condition<F> {
+    fn get_status(&self) -> Status {
+        self.status
+    }
+    fn set_status(&mut self, s: Status) {
+        self.status = s;
+    }
+    fn update(&mut self, ctx: &mut Ctx) -> Status {
+        if (self.condition)(ctx) { Status::Success } else { Status::Failure }
+    }
+}
+
+/// 依次执行子节点的序列节点，语义与 [`crate::Sequence`] 相同
+pub struct Sequence<Ctx> {
+    status: Status,
+    children: Vec<Box<dyn ContextNode<Ctx>>>,
+    current: usize,
+}
+
+impl<Ctx> Sequence<Ctx> {
+    pub fn new(children: Vec<Box<dyn ContextNode<Ctx>>>) -> Self {
+        Self {
+            status: Status::Invalid,
+            children,
+            current: 0,
+        }
+    }
+
+    pub fn add_child(&mut self, child: Box<dyn ContextNode<Ctx>>) {
+        self.children.push(child);
+    }
+}
+
+impl<Ctx> ContextNode<Ctx> for Sequence<Ctx> {
+    fn get_status(&self) -> Status {
+        self.status
+    }
+    fn set_status(&mut self, s: Status) {
+        self.status = s;
+    }
+    fn halt(&mut self, ctx: &mut Ctx) {
+        for child in self.children.iter_mut() {
+            child.halt(ctx);
+        }
+        if self.is_running() {
+            self.terminate(ctx);
+        }
+        self.reset();
+    }
+
+    fn initialize(&mut self, _ctx: &mut Ctx) {
+        self.current = 0;
+    }
+
+    fn update(&mut self, ctx: &mut Ctx) -> Status {
+        while self.current < self.children.len() {
+            match self.children[self.current].tick(ctx) {
+                Status::Running => return Status::Running,
+                Status::Failure => return Status::Failure,
+                Status::Success => self.current += 1,
+                Status::Invalid => return Status::Invalid,
+            }
+        }
+        Status::Success
+    }
+}
+
+/// 依次尝试子节点直到有一个成功的选择节点，语义与 [`crate::Selector`] 相同
+pub struct Selector<Ctx> {
+    status: Status,
+    children: Vec<Box<dyn ContextNode<Ctx>>>,
+    current: usize,
+}
+
+impl<Ctx> Selector<Ctx> {
+    pub fn new(children: Vec<Box<dyn ContextNode<Ctx>>>) -> Self {
+        Self {
+            status: Status::Invalid,
+            children,
+            current: 0,
+        }
+    }
+
+    pub fn add_child(&mut self, child: Box<dyn ContextNode<Ctx>>) {
+        self.children.push(child);
+    }
+}
+
+impl<Ctx> ContextNode<Ctx> for Selector<Ctx> {
+    fn get_status(&self) -> Status {
+        self.status
+    }
+    fn set_status(&mut self, s: Status) {
+        self.status = s;
+    }
+    fn halt(&mut self, ctx: &mut Ctx) {
+        for child in self.children.iter_mut() {
+            child.halt(ctx);
+        }
+        if self.is_running() {
+            self.terminate(ctx);
+        }
+        self.reset();
+    }
+
+    fn initialize(&mut self, _ctx: &mut Ctx) {
+        self.current = 0;
+    }
+
+    fn update(&mut self, ctx: &mut Ctx) -> Status {
+        while self.current < self.children.len() {
+            match self.children[self.current].tick(ctx) {
+                Status::Running => return Status::Running,
+                Status::Success => return Status::Success,
+                Status::Failure => self.current += 1,
+                Status::Invalid => return Status::Invalid,
+            }
+        }
+        Status::Failure
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // 测试用的上下文：电量和执行日志
+    #[derive(Default)]
+    struct Robot {
+        battery: u32,
+        log: Vec<&'static str>,
+    }
+
+    // 电量不低于 min 时成功
+    struct BatteryAbove {
+        status: Status,
+        min: u32,
+    }
+
+    // 消耗 cost 电量并记录动作名
+    struct Drive {
+        status: Status,
+        name: &'static str,
+        cost: u32,
+    }
+
+    impl ContextNode<Robot> for BatteryAbove {
+        fn get_status(&self) -> Status {
+            self.status
+        }
+        fn set_status(&mut self, s: Status) {
+            self.status = s;
+        }
+        fn update(&mut self, ctx: &mut Robot) -> Status {
+            if ctx.battery >= self.min { Status::Success } else { Status::Failure }
+        }
+    }
+
+    impl ContextNode<Robot> for Drive {
+        fn get_status(&self) -> Status {
+            self.status
+        }
+        fn set_status(&mut self, s: Status) {
+            self.status = s;
+        }
+        fn update(&mut self, ctx: &mut Robot) -> Status {
+            ctx.battery -= self.cost;
+            ctx.log.push(self.name);
+            Status::Success
+        }
+    }
+
+    fn battery_above(min: u32) -> Box<dyn ContextNode<Robot>> {
+        Box::new(BatteryAbove { status: Status::Invalid, min })
+    }
+
+    fn drive(name: &'static str, cost: u32) -> Box<dyn ContextNode<Robot>> {
+        Box::new(Drive { status: Status::Invalid, name, cost })
+    }
+
+    #[test]
+    fn selector_falls_back_when_sequence_fails() {
+        // Selector( Sequence(电量 >= 50, 远行), 近行 )
+        let mut root = Selector::new(vec![
+            Box::new(Sequence::new(vec![battery_above(50), drive("far", 40)])),
+            drive("near", 10),
+        ]);
+
+        let mut robot = Robot { battery: 80, ..Default::default() };
+        assert_eq!(root.tick(&mut robot), Status::Success);
+        assert_eq!(root.tick(&mut robot), Status::Success);
+        assert_eq!(robot.log, ["far", "near"]);
+        assert_eq!(robot.battery, 30);
+    }
+
+    #[test]
+    fn closure_leaves_read_and_write_context() {
+        let mut root: Sequence<Robot> = Sequence::new(vec![
+            Box::new(Condition::new(|robot: &Robot| robot.battery > 0)),
+            Box::new(Action::new(|robot: &mut Robot| {
+                robot.battery -= 1;
+                if robot.battery == 0 { Status::Success } else { Status::Running }
+            })),
+        ]);
+
+        let mut robot = Robot { battery: 2, ..Default::default() };
+        assert_eq!(root.tick(&mut robot), Status::Running);
+        assert_eq!(root.tick(&mut robot), Status::Success);
+        // 电量耗尽后条件不再成立
+        assert_eq!(root.tick(&mut robot), Status::Failure);
+        assert_eq!(robot.battery, 0);
+    }
+}
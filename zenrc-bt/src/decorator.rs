@@ -0,0 +1,821 @@
+//! 装饰节点：包装单个子节点并改变其执行方式或返回结果
+use std::cell::RefCell;
+use std::rc::Rc;
+use std::time::{Duration, Instant};
+
+use crate::{BaseNode, BlackboardPtr, Node, Status};
+
+/// 超时装饰器（按 tick 计数）
+///
+/// 从 initialize() 开始计数，子节点在 max_ticks 次 tick 后仍为 Running 时，
+/// 中断子节点并返回 on_timeout。按 tick 而非墙钟计时，仿真中结果确定。
+pub struct MaxTicks {
+    base: BaseNode,
+    child: Box<dyn Node>,
+    max_ticks: usize,
+    on_timeout: Status,
+    ticks: usize,
+}
+impl MaxTicks {
+    pub fn new(child: Box<dyn Node>, max_ticks: usize, on_timeout: Status) -> Self {
+        debug_assert!(
+            matches!(on_timeout, Status::Success | Status::Failure),
+            "on_timeout must be a terminal status"
+        );
+        Self {
+            base: BaseNode::new(),
+            child,
+            max_ticks,
+            on_timeout,
+            ticks: 0,
+        }
+    }
+}
+impl Node for MaxTicks {
+    fn children(&self) -> &[Box<dyn Node>] {
+        std::slice::from_ref(&self.child)
+    }
+    fn children_mut(&mut self) -> &mut [Box<dyn Node>] {
+        std::slice::from_mut(&mut self.child)
+    }
+    fn get_blackboard(&self) -> Option<BlackboardPtr> {
+        self.base.get_blackboard()
+    }
+    fn set_blackboard(&mut self, bb: BlackboardPtr) {
+        self.base.set_blackboard(bb.clone());
+        self.child.set_blackboard(bb);
+    }
+    fn get_status(&self) -> Status {
+        self.base.get_status()
+    }
+    fn set_status(&mut self, s: Status) {
+        self.base.set_status(s);
+    }
+    fn clone_node(&self) -> Box<dyn Node> {
+        Box::new(MaxTicks::new(self.child.clone_node(), self.max_ticks, self.on_timeout))
+    }
+    fn initialize(&mut self) {
+        self.ticks = 0;
+    }
+    fn update(&mut self) -> Status {
+        if self.ticks >= self.max_ticks {
+            self.child.halt();
+            return self.on_timeout;
+        }
+        self.ticks += 1;
+        self.child.tick()
+    }
+}
+
+// Guard 的条件闭包，副本之间共享
+type Condition = Rc<RefCell<dyn FnMut(&BlackboardPtr) -> bool>>;
+
+/// 前置条件守卫：每次 update() 先检查条件，条件成立才 tick 子节点
+///
+/// 子节点 Running 期间条件变为不成立时，中断子节点并返回 Failure，
+/// 即“在 Y 成立期间持续执行 X”。未设置黑板时视为条件不成立。
+/// `clone_node` 得到的副本与原节点共享同一个条件闭包（包括闭包捕获的状态）。
+pub struct Guard {
+    base: BaseNode,
+    child: Box<dyn Node>,
+    condition: Condition,
+}
+impl Guard {
+    pub fn new(
+        condition: impl FnMut(&BlackboardPtr) -> bool + 'static,
+        child: Box<dyn Node>,
+    ) -> Self {
+        Self {
+            base: BaseNode::new(),
+            child,
+            condition: Rc::new(RefCell::new(condition)),
+        }
+    }
+}
+impl Node for Guard {
+    fn children(&self) -> &[Box<dyn Node>] {
+        std::slice::from_ref(&self.child)
+    }
+    fn children_mut(&mut self) -> &mut [Box<dyn Node>] {
+        std::slice::from_mut(&mut self.child)
+    }
+    fn get_blackboard(&self) -> Option<BlackboardPtr> {
+        self.base.get_blackboard()
+    }
+    fn set_blackboard(&mut self, bb: BlackboardPtr) {
+        self.base.set_blackboard(bb.clone());
+        self.child.set_blackboard(bb);
+    }
+    fn get_status(&self) -> Status {
+        self.base.get_status()
+    }
+    fn set_status(&mut self, s: Status) {
+        self.base.set_status(s);
+    }
+    fn clone_node(&self) -> Box<dyn Node> {
+        Box::new(Guard {
+            base: BaseNode::new(),
+            child: self.child.clone_node(),
+            condition: self.condition.clone(),
+        })
+    }
+    fn update(&mut self) -> Status {
+        let holds = match self.get_blackboard() {
+            Some(bb) => (self.condition.borrow_mut())(&bb),
+            None => false,
+        };
+        if !holds {
+            self.child.halt();
+            return Status::Failure;
+        }
+        self.child.tick()
+    }
+}
+
+/// 子节点结束时的回调，参数为子节点的最终状态和黑板
+pub type ResultCallback = Box<dyn FnMut(Status, &BlackboardPtr)>;
+
+/// 结果回调装饰器：子节点返回 Success/Failure 时调用对应回调，返回值原样透传
+///
+/// 每次子节点结束只调用一次，Running 期间不调用；未设置黑板时新建一个空黑板传给回调，
+/// 见 [`Node::blackboard_or_default`]。
+/// `clone_node` 得到的副本与原节点共享同一组回调（包括闭包捕获的状态）。
+pub struct OnResult {
+    base: BaseNode,
+    child: Box<dyn Node>,
+    on_success: Option<Rc<RefCell<ResultCallback>>>,
+    on_failure: Option<Rc<RefCell<ResultCallback>>>,
+}
+impl OnResult {
+    pub fn new(
+        child: Box<dyn Node>,
+        on_success: Option<ResultCallback>,
+        on_failure: Option<ResultCallback>,
+    ) -> Self {
+        Self {
+            base: BaseNode::new(),
+            child,
+            on_success: on_success.map(|callback| Rc::new(RefCell::new(callback))),
+            on_failure: on_failure.map(|callback| Rc::new(RefCell::new(callback))),
+        }
+    }
+}
+impl Node for OnResult {
+    fn children(&self) -> &[Box<dyn Node>] {
+        std::slice::from_ref(&self.child)
+    }
+    fn children_mut(&mut self) -> &mut [Box<dyn Node>] {
+        std::slice::from_mut(&mut self.child)
+    }
+    fn get_blackboard(&self) -> Option<BlackboardPtr> {
+        self.base.get_blackboard()
+    }
+    fn set_blackboard(&mut self, bb: BlackboardPtr) {
+        self.base.set_blackboard(bb.clone());
+        self.child.set_blackboard(bb);
+    }
+    fn get_status(&self) -> Status {
+        self.base.get_status()
+    }
+    fn set_status(&mut self, s: Status) {
+        self.base.set_status(s);
+    }
+    fn clone_node(&self) -> Box<dyn Node> {
+        Box::new(OnResult {
+            base: BaseNode::new(),
+            child: self.child.clone_node(),
+            on_success: self.on_success.clone(),
+            on_failure: self.on_failure.clone(),
+        })
+    }
+    fn update(&mut self) -> Status {
+        let status = self.child.tick();
+        let callback = match status {
+            Status::Success => self.on_success.clone(),
+            Status::Failure => self.on_failure.clone(),
+            _ => None,
+        };
+        if let Some(callback) = callback {
+            let bb = self.blackboard_or_default();
+            (callback.borrow_mut())(status, &bb);
+        }
+        status
+    }
+}
+
+/// 冷却装饰器：子节点成功后的 cooldown 时间内不再 tick 子节点，直接返回 on_cooldown
+///
+/// 默认 on_cooldown 为 Failure。冷却只影响新的执行：子节点 Running 期间
+/// 即使跨过冷却窗口也会继续 tick，不会被中断。
+pub struct Cooldown {
+    base: BaseNode,
+    child: Box<dyn Node>,
+    cooldown: Duration,
+    on_cooldown: Status,
+    last_success: Option<Instant>,
+}
+impl Cooldown {
+    pub fn new(child: Box<dyn Node>, cooldown: Duration) -> Self {
+        Self {
+            base: BaseNode::new(),
+            child,
+            cooldown,
+            on_cooldown: Status::Failure,
+            last_success: None,
+        }
+    }
+    /// 设置冷却期间返回的状态
+    pub fn on_cooldown(self, status: Status) -> Self {
+        Self {
+            on_cooldown: status,
+            ..self
+        }
+    }
+    fn cooling_down(&self) -> bool {
+        self.last_success.is_some_and(|at| at.elapsed() < self.cooldown)
+    }
+}
+impl Node for Cooldown {
+    fn children(&self) -> &[Box<dyn Node>] {
+        std::slice::from_ref(&self.child)
+    }
+    fn children_mut(&mut self) -> &mut [Box<dyn Node>] {
+        std::slice::from_mut(&mut self.child)
+    }
+    fn get_blackboard(&self) -> Option<BlackboardPtr> {
+        self.base.get_blackboard()
+    }
+    fn set_blackboard(&mut self, bb: BlackboardPtr) {
+        self.base.set_blackboard(bb.clone());
+        self.child.set_blackboard(bb);
+    }
+    fn get_status(&self) -> Status {
+        self.base.get_status()
+    }
+    fn set_status(&mut self, s: Status) {
+        self.base.set_status(s);
+    }
+    fn clone_node(&self) -> Box<dyn Node> {
+        let node = Cooldown::new(self.child.clone_node(), self.cooldown);
+        Box::new(node.on_cooldown(self.on_cooldown))
+    }
+    fn update(&mut self) -> Status {
+        if !self.child.is_running() && self.cooling_down() {
+            return self.on_cooldown;
+        }
+        let status = self.child.tick();
+        if status == Status::Success {
+            self.last_success = Some(Instant::now());
+        }
+        status
+    }
+}
+
+/// 只执行一次：子节点第一次结束（Success/Failure）后缓存结果，之后每次 tick 直接返回缓存
+///
+/// 适用于只需执行一次的初始化步骤。调用 reset() 清空缓存后可再次执行；
+/// halt() 只中断正在运行的子节点，不清空已缓存的结果。
+pub struct Once {
+    base: BaseNode,
+    child: Box<dyn Node>,
+    result: Option<Status>,
+}
+impl Once {
+    pub fn new(child: Box<dyn Node>) -> Self {
+        Self {
+            base: BaseNode::new(),
+            child,
+            result: None,
+        }
+    }
+}
+impl Node for Once {
+    fn children(&self) -> &[Box<dyn Node>] {
+        std::slice::from_ref(&self.child)
+    }
+    fn children_mut(&mut self) -> &mut [Box<dyn Node>] {
+        std::slice::from_mut(&mut self.child)
+    }
+    fn get_blackboard(&self) -> Option<BlackboardPtr> {
+        self.base.get_blackboard()
+    }
+    fn set_blackboard(&mut self, bb: BlackboardPtr) {
+        self.base.set_blackboard(bb.clone());
+        self.child.set_blackboard(bb);
+    }
+    fn get_status(&self) -> Status {
+        self.base.get_status()
+    }
+    fn set_status(&mut self, s: Status) {
+        self.base.set_status(s);
+    }
+    fn reset(&mut self) {
+        self.result = None;
+        self.set_status(Status::Invalid);
+    }
+    fn halt(&mut self) {
+        self.interrupt();
+        self.set_status(Status::Invalid);
+    }
+    fn clone_node(&self) -> Box<dyn Node> {
+        Box::new(Once::new(self.child.clone_node()))
+    }
+    fn update(&mut self) -> Status {
+        if let Some(status) = self.result {
+            return status;
+        }
+        let status = self.child.tick();
+        if status != Status::Running {
+            self.result = Some(status);
+        }
+        status
+    }
+}
+
+/// 重复执行直到失败：子节点成功后重置并在下一次 tick 再次执行，期间返回 Running
+///
+/// 子节点失败时返回 Success；成功次数达到 max 时返回最后一次的状态（Success）。
+/// max 为 0 表示不限次数。每次 tick 最多执行子节点一次，重新进入时计数清零。
+pub struct RepeatUntilFailure {
+    base: BaseNode,
+    child: Box<dyn Node>,
+    max: usize,
+    count: usize,
+}
+impl RepeatUntilFailure {
+    pub fn new(child: Box<dyn Node>, max: usize) -> Self {
+        Self {
+            base: BaseNode::new(),
+            child,
+            max,
+            count: 0,
+        }
+    }
+}
+impl Node for RepeatUntilFailure {
+    fn children(&self) -> &[Box<dyn Node>] {
+        std::slice::from_ref(&self.child)
+    }
+    fn children_mut(&mut self) -> &mut [Box<dyn Node>] {
+        std::slice::from_mut(&mut self.child)
+    }
+    fn get_blackboard(&self) -> Option<BlackboardPtr> {
+        self.base.get_blackboard()
+    }
+    fn set_blackboard(&mut self, bb: BlackboardPtr) {
+        self.base.set_blackboard(bb.clone());
+        self.child.set_blackboard(bb);
+    }
+    fn get_status(&self) -> Status {
+        self.base.get_status()
+    }
+    fn set_status(&mut self, s: Status) {
+        self.base.set_status(s);
+    }
+    fn clone_node(&self) -> Box<dyn Node> {
+        Box::new(RepeatUntilFailure::new(self.child.clone_node(), self.max))
+    }
+    fn initialize(&mut self) {
+        self.count = 0;
+    }
+    fn update(&mut self) -> Status {
+        match self.child.tick() {
+            Status::Failure => Status::Success,
+            Status::Success => {
+                self.count += 1;
+                if self.max != 0 && self.count >= self.max {
+                    return Status::Success;
+                }
+                self.child.reset();
+                Status::Running
+            }
+            status => status,
+        }
+    }
+}
+
+/// 强制得到终止结果：子节点返回 Running 时中断子节点并返回 running_maps_to
+///
+/// Success/Failure 原样转发，用于只 tick 一次、需要确定结果的调用方（如一次性的规划调用）。
+pub struct ForceResult {
+    base: BaseNode,
+    child: Box<dyn Node>,
+    running_maps_to: Status,
+}
+impl ForceResult {
+    pub fn new(child: Box<dyn Node>, running_maps_to: Status) -> Self {
+        debug_assert!(
+            matches!(running_maps_to, Status::Success | Status::Failure),
+            "running_maps_to must be a terminal status"
+        );
+        Self {
+            base: BaseNode::new(),
+            child,
+            running_maps_to,
+        }
+    }
+}
+impl Node for ForceResult {
+    fn children(&self) -> &[Box<dyn Node>] {
+        std::slice::from_ref(&self.child)
+    }
+    fn children_mut(&mut self) -> &mut [Box<dyn Node>] {
+        std::slice::from_mut(&mut self.child)
+    }
+    fn get_blackboard(&self) -> Option<BlackboardPtr> {
+        self.base.get_blackboard()
+    }
+    fn set_blackboard(&mut self, bb: BlackboardPtr) {
+        self.base.set_blackboard(bb.clone());
+        self.child.set_blackboard(bb);
+    }
+    fn get_status(&self) -> Status {
+        self.base.get_status()
+    }
+    fn set_status(&mut self, s: Status) {
+        self.base.set_status(s);
+    }
+    fn clone_node(&self) -> Box<dyn Node> {
+        Box::new(ForceResult::new(self.child.clone_node(), self.running_maps_to))
+    }
+    fn update(&mut self) -> Status {
+        match self.child.tick() {
+            Status::Running => {
+                self.child.halt();
+                self.running_maps_to
+            }
+            status => status,
+        }
+    }
+}
+
+/// 降频装饰器：每 every_n_ticks 次 update() 才 tick 一次子节点，其余时候返回子节点上一次的状态
+///
+/// 用于重规划等开销较大的子树。子节点 Running 时每次都 tick，不会跳过；
+/// 计数跨越多次执行累计，halt() 后清零，下一次 update() 立即 tick 子节点。
+pub struct Throttle {
+    base: BaseNode,
+    child: Box<dyn Node>,
+    every_n_ticks: usize,
+    calls: usize,
+    last: Status,
+}
+impl Throttle {
+    pub fn new(child: Box<dyn Node>, every_n_ticks: usize) -> Self {
+        debug_assert!(every_n_ticks > 0, "every_n_ticks must be positive");
+        Self {
+            base: BaseNode::new(),
+            child,
+            every_n_ticks: every_n_ticks.max(1),
+            calls: 0,
+            last: Status::Invalid,
+        }
+    }
+}
+impl Node for Throttle {
+    fn children(&self) -> &[Box<dyn Node>] {
+        std::slice::from_ref(&self.child)
+    }
+    fn children_mut(&mut self) -> &mut [Box<dyn Node>] {
+        std::slice::from_mut(&mut self.child)
+    }
+    fn get_blackboard(&self) -> Option<BlackboardPtr> {
+        self.base.get_blackboard()
+    }
+    fn set_blackboard(&mut self, bb: BlackboardPtr) {
+        self.base.set_blackboard(bb.clone());
+        self.child.set_blackboard(bb);
+    }
+    fn get_status(&self) -> Status {
+        self.base.get_status()
+    }
+    fn set_status(&mut self, s: Status) {
+        self.base.set_status(s);
+    }
+    fn halt(&mut self) {
+        self.interrupt();
+        self.calls = 0;
+        self.last = Status::Invalid;
+        self.reset();
+    }
+    fn clone_node(&self) -> Box<dyn Node> {
+        Box::new(Throttle::new(self.child.clone_node(), self.every_n_ticks))
+    }
+    fn update(&mut self) -> Status {
+        let due = self.calls.is_multiple_of(self.every_n_ticks);
+        self.calls += 1;
+        if due || self.last == Status::Running || self.last == Status::Invalid {
+            self.last = self.child.tick();
+        }
+        self.last
+    }
+}
+
+/// [`Measured`] 写入黑板的执行统计
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct NodeMetrics {
+    /// 最近一次执行从首次 tick 到结束经过的时间
+    pub last_duration: Duration,
+    /// 子节点累计被 tick 的次数
+    pub tick_count: u64,
+    /// 子节点累计返回 Success 的次数
+    pub success_count: u64,
+}
+
+/// 执行统计装饰器：子节点每次结束时把 [`NodeMetrics`] 写入黑板的 key，返回值原样透传
+///
+/// 统计在装饰器内累计，同级的条件节点可读取 key 对执行情况作出反应；未设置黑板时只累计不写入。
+pub struct Measured {
+    base: BaseNode,
+    child: Box<dyn Node>,
+    key: String,
+    metrics: NodeMetrics,
+    started: Option<Instant>,
+}
+impl Measured {
+    pub fn new(child: Box<dyn Node>, key: &str) -> Self {
+        Self {
+            base: BaseNode::new(),
+            child,
+            key: key.to_string(),
+            metrics: NodeMetrics::default(),
+            started: None,
+        }
+    }
+
+    /// 当前累计的统计
+    pub fn metrics(&self) -> NodeMetrics {
+        self.metrics
+    }
+}
+impl Node for Measured {
+    fn children(&self) -> &[Box<dyn Node>] {
+        std::slice::from_ref(&self.child)
+    }
+    fn children_mut(&mut self) -> &mut [Box<dyn Node>] {
+        std::slice::from_mut(&mut self.child)
+    }
+    fn get_blackboard(&self) -> Option<BlackboardPtr> {
+        self.base.get_blackboard()
+    }
+    fn set_blackboard(&mut self, bb: BlackboardPtr) {
+        self.base.set_blackboard(bb.clone());
+        self.child.set_blackboard(bb);
+    }
+    fn get_status(&self) -> Status {
+        self.base.get_status()
+    }
+    fn set_status(&mut self, s: Status) {
+        self.base.set_status(s);
+    }
+    fn halt(&mut self) {
+        self.interrupt();
+        self.started = None;
+        self.reset();
+    }
+    fn clone_node(&self) -> Box<dyn Node> {
+        Box::new(Measured::new(self.child.clone_node(), &self.key))
+    }
+    fn update(&mut self) -> Status {
+        let started = *self.started.get_or_insert_with(Instant::now);
+        let status = self.child.tick();
+        self.metrics.tick_count += 1;
+        if status == Status::Running {
+            return status;
+        }
+
+        self.started = None;
+        self.metrics.last_duration = started.elapsed();
+        if status == Status::Success {
+            self.metrics.success_count += 1;
+        }
+        if let Some(bb) = self.get_blackboard() {
+            bb.set(&self.key, self.metrics);
+        }
+        status
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::ScriptNode;
+    use std::cell::Cell;
+
+    #[test]
+    fn max_ticks_times_out_running_child() {
+        let child = ScriptNode::new(&[Status::Running]);
+        let probe = child.probe();
+        let mut node = MaxTicks::new(Box::new(child), 3, Status::Failure);
+
+        for _ in 0..3 {
+            assert_eq!(node.tick(), Status::Running);
+        }
+        assert_eq!(node.tick(), Status::Failure);
+        assert_eq!(probe.updates(), 3);
+        assert_eq!(probe.terminates(), 1);
+
+        // 重新进入后计数从零开始
+        assert_eq!(node.tick(), Status::Running);
+        assert_eq!(probe.updates(), 4);
+    }
+
+    #[test]
+    fn guard_halts_running_child_when_condition_fails() {
+        let child = ScriptNode::new(&[Status::Running]);
+        let probe = child.probe();
+        let mut node = Guard::new(
+            |bb| bb.get::<bool>("enabled").is_some_and(|enabled| *enabled),
+            Box::new(child),
+        );
+        let bb = BlackboardPtr::new();
+        bb.borrow_mut().insert("enabled".to_string(), Box::new(true));
+        node.set_blackboard(bb.clone());
+
+        assert_eq!(node.tick(), Status::Running);
+        assert_eq!(node.tick(), Status::Running);
+        assert_eq!(probe.terminates(), 0);
+
+        bb.borrow_mut().insert("enabled".to_string(), Box::new(false));
+        assert_eq!(node.tick(), Status::Failure);
+        assert_eq!(probe.updates(), 2);
+        assert_eq!(probe.terminates(), 1);
+    }
+
+    #[test]
+    fn templates_with_closures_can_be_cloned() {
+        let guarded = Guard::new(
+            |bb| bb.get_bool("enabled") == Some(true),
+            Box::new(ScriptNode::new(&[Status::Success])),
+        );
+        let on_success: ResultCallback = Box::new(|_, bb| {
+            *bb.entry_or_insert_with("done", || 0) += 1;
+        });
+        let template = crate::Sequence::new(vec![
+            Box::new(guarded),
+            Box::new(OnResult::new(
+                Box::new(ScriptNode::new(&[Status::Success])),
+                Some(on_success),
+                None,
+            )),
+        ]);
+
+        let bb = BlackboardPtr::new();
+        let mut copy = template.clone_node();
+        copy.set_blackboard(bb.clone());
+        assert_eq!(copy.tick(), Status::Failure);
+        bb.set("enabled", true);
+        assert_eq!(copy.tick(), Status::Success);
+        assert_eq!(*bb.get::<i32>("done").unwrap(), 1);
+    }
+
+    #[test]
+    fn on_result_fires_once_per_termination() {
+        let child = ScriptNode::new(&[Status::Running, Status::Running, Status::Success]);
+        let on_success: ResultCallback = Box::new(|status, bb| {
+            assert_eq!(status, Status::Success);
+            *bb.entry_or_insert_with("done", || 0) += 1;
+        });
+        let mut node = OnResult::new(Box::new(child), Some(on_success), None);
+        let bb = BlackboardPtr::new();
+        node.set_blackboard(bb.clone());
+
+        assert_eq!(node.tick(), Status::Running);
+        assert_eq!(node.tick(), Status::Running);
+        assert!(bb.get::<i32>("done").is_none());
+        assert_eq!(node.tick(), Status::Success);
+        assert_eq!(*bb.get::<i32>("done").unwrap(), 1);
+    }
+
+    #[test]
+    fn on_result_fires_without_blackboard() {
+        let fired = Rc::new(Cell::new(0));
+        let counter = fired.clone();
+        let on_failure: ResultCallback = Box::new(move |_, _| counter.set(counter.get() + 1));
+        let child = ScriptNode::new(&[Status::Failure]);
+        let mut node = OnResult::new(Box::new(child), None, Some(on_failure));
+
+        assert_eq!(node.tick(), Status::Failure);
+        assert_eq!(fired.get(), 1);
+    }
+
+    #[test]
+    fn cooldown_skips_child_within_window() {
+        let child = ScriptNode::new(&[Status::Success]);
+        let probe = child.probe();
+        let mut node = Cooldown::new(Box::new(child), Duration::from_millis(20));
+
+        assert_eq!(node.tick(), Status::Success);
+        assert_eq!(node.tick(), Status::Failure);
+        assert_eq!(probe.updates(), 1);
+
+        std::thread::sleep(Duration::from_millis(25));
+        assert_eq!(node.tick(), Status::Success);
+        assert_eq!(probe.updates(), 2);
+    }
+
+    #[test]
+    fn once_ticks_child_a_single_time() {
+        let child = ScriptNode::new(&[Status::Running, Status::Success, Status::Failure]);
+        let probe = child.probe();
+        let mut node = Once::new(Box::new(child));
+
+        assert_eq!(node.tick(), Status::Running);
+        for _ in 0..3 {
+            assert_eq!(node.tick(), Status::Success);
+        }
+        assert_eq!(probe.updates(), 2);
+
+        node.reset();
+        assert_eq!(node.tick(), Status::Failure);
+        assert_eq!(probe.updates(), 3);
+    }
+
+    #[test]
+    fn repeat_until_failure_succeeds_when_child_fails() {
+        let child = ScriptNode::new(&[Status::Success, Status::Success, Status::Failure]);
+        let probe = child.probe();
+        let mut node = RepeatUntilFailure::new(Box::new(child), 0);
+
+        assert_eq!(node.tick(), Status::Running);
+        assert_eq!(node.tick(), Status::Running);
+        assert_eq!(node.tick(), Status::Success);
+        assert_eq!(probe.updates(), 3);
+
+        let mut bounded = RepeatUntilFailure::new(Box::new(ScriptNode::new(&[Status::Success])), 2);
+        assert_eq!(bounded.tick(), Status::Running);
+        assert_eq!(bounded.tick(), Status::Success);
+    }
+
+    #[test]
+    fn force_result_halts_running_child() {
+        let child = ScriptNode::new(&[Status::Running, Status::Success]);
+        let probe = child.probe();
+        let mut node = ForceResult::new(Box::new(child), Status::Failure);
+
+        assert_eq!(node.tick(), Status::Failure);
+        assert_eq!(probe.terminates(), 1);
+        assert_eq!(node.children()[0].get_status(), Status::Invalid);
+
+        // 终止结果原样转发
+        assert_eq!(node.tick(), Status::Success);
+        assert_eq!(probe.updates(), 2);
+    }
+
+    #[test]
+    fn measured_accumulates_metrics_in_blackboard() {
+        let child = ScriptNode::new(&[
+            Status::Running,
+            Status::Success,
+            Status::Failure,
+            Status::Success,
+        ]);
+        let mut node = Measured::new(Box::new(child), "patrol.metrics");
+        let bb = BlackboardPtr::new();
+        node.set_blackboard(bb.clone());
+
+        assert_eq!(node.tick(), Status::Running);
+        // 子节点结束前不写入
+        assert!(bb.get::<NodeMetrics>("patrol.metrics").is_none());
+        assert_eq!(node.tick(), Status::Success);
+        assert_eq!(node.tick(), Status::Failure);
+        assert_eq!(node.tick(), Status::Success);
+
+        let metrics = *bb.get::<NodeMetrics>("patrol.metrics").unwrap();
+        assert_eq!(metrics.tick_count, 4);
+        assert_eq!(metrics.success_count, 2);
+        assert_eq!(metrics, node.metrics());
+    }
+
+    #[test]
+    fn throttle_ticks_child_every_n_updates() {
+        let child = ScriptNode::new(&[Status::Success]);
+        let probe = child.probe();
+        let mut node = Throttle::new(Box::new(child), 3);
+
+        let mut ticked_on = Vec::new();
+        for tick in 1..=8 {
+            let before = probe.updates();
+            assert_eq!(node.tick(), Status::Success);
+            if probe.updates() > before {
+                ticked_on.push(tick);
+            }
+        }
+        assert_eq!(ticked_on, [1, 4, 7]);
+    }
+
+    #[test]
+    fn throttle_keeps_ticking_running_child() {
+        let child = ScriptNode::new(&[Status::Running, Status::Running, Status::Success]);
+        let probe = child.probe();
+        let mut node = Throttle::new(Box::new(child), 4);
+
+        assert_eq!(node.tick(), Status::Running);
+        assert_eq!(node.tick(), Status::Running);
+        assert_eq!(node.tick(), Status::Success);
+        assert_eq!(probe.updates(), 3);
+        // 子节点结束后才开始跳过
+        assert_eq!(node.tick(), Status::Success);
+        assert_eq!(probe.updates(), 3);
+    }
+}
@@ -0,0 +1,1857 @@
+//! 以黑板共享数据的行为树节点与复合节点，依赖 std，仅在启用 `std` feature 时可用
+use std::rc::Rc;
+
+use thiserror::Error;
+
+use crate::port::{self, PortError, PortSpec};
+use crate::rng::SplitMix64;
+use crate::{BlackboardPtr, Status, runner, trace};
+
+/// 把具体节点转换为 `&dyn Node`，供 Node 的默认方法在遍历时使用；对所有节点自动实现
+pub trait AsNode {
+    fn as_node(&self) -> &dyn Node;
+}
+impl<T: Node> AsNode for T {
+    fn as_node(&self) -> &dyn Node {
+        self
+    }
+}
+
+/// 行为树节点 Trait
+pub trait Node: AsNode {
+    /// 获取黑板
+    fn get_blackboard(&self) -> Option<BlackboardPtr>;
+    /// 设置黑板
+    fn set_blackboard(&mut self, bb: BlackboardPtr);
+
+    /// 获取黑板，未设置时新建一个空黑板并设置到本节点上
+    ///
+    /// 新建的是孤立黑板，只属于本节点（复合节点会同时设置给子节点），与所在树的其他节点不共享；
+    /// 主要用于在没有外围树的单元测试中直接 tick 叶子节点。
+    fn blackboard_or_default(&mut self) -> BlackboardPtr {
+        match self.get_blackboard() {
+            Some(bb) => bb,
+            None => {
+                let bb = BlackboardPtr::new();
+                self.set_blackboard(bb.clone());
+                bb
+            }
+        }
+    }
+
+    /// 每个节点必须实现 update()
+    fn update(&mut self) -> Status;
+
+    /// 可以覆盖：初始化
+    fn initialize(&mut self) {}
+    /// 可以覆盖：结束时调用
+    fn terminate(&mut self) {}
+
+    /// 深拷贝节点，用于从模板子树实例化多个副本
+    ///
+    /// 副本的状态重置为 Invalid、内部游标归零，且不带黑板（需重新 `set_blackboard`）。
+    /// 复合节点会递归克隆子节点；叶子节点需自行实现，默认实现直接 panic。
+    fn clone_node(&self) -> Box<dyn Node> {
+        panic!("{} does not implement clone_node", self.name())
+    }
+
+    /// 节点读写的黑板键及其类型，默认不声明；用于 [`BehaviorTree::validate_ports`] 提前发现接线错误
+    fn ports(&self) -> Vec<PortSpec> {
+        Vec::new()
+    }
+
+    /// 节点只依赖的黑板键，默认为空
+    ///
+    /// 由 [`ReactiveRunner`](crate::runner::ReactiveRunner) 执行时，声明了监听键的节点已结束
+    /// （Success/Failure）且这些键自上次 tick 以来没有被写入，则直接返回上次的状态，
+    /// 不再执行其子树。为空时总是正常执行。
+    fn watch_keys(&self) -> &[&str] {
+        &[]
+    }
+
+    /// 子节点列表，叶子节点为空；复合节点和装饰节点覆盖此方法以支持遍历
+    fn children(&self) -> &[Box<dyn Node>] {
+        &[]
+    }
+
+    /// 可变的子节点列表，与 [`children`](Node::children) 一一对应，用于需要修改子树的遍历
+    fn children_mut(&mut self) -> &mut [Box<dyn Node>] {
+        &mut []
+    }
+
+    /// 跨执行保留的游标（下一次从哪个子节点开始），只有 [`StatefulSequence`] 等节点返回 Some
+    ///
+    /// 用于 [`BehaviorTree::save_cursors`] 保存执行进度。
+    fn cursor(&self) -> Option<usize> {
+        None
+    }
+
+    /// 设置游标，越界或节点没有游标时返回 Err(index)
+    fn set_cursor(&mut self, index: usize) -> Result<(), usize> {
+        Err(index)
+    }
+
+    /// 先序遍历以本节点为根的子树，f 的第二个参数为深度（本节点为 0）
+    fn visit(&self, f: &mut dyn FnMut(&dyn Node, usize)) {
+        fn walk(node: &dyn Node, depth: usize, f: &mut dyn FnMut(&dyn Node, usize)) {
+            f(node, depth);
+            for child in node.children() {
+                walk(child.as_ref(), depth + 1, f);
+            }
+        }
+        walk(self.as_node(), 0, f);
+    }
+
+    /// 按先序查找第一个名称为 name 的节点（含本节点）
+    fn find(&self, name: &str) -> Option<&dyn Node> {
+        if self.name() == name {
+            return Some(self.as_node());
+        }
+        self.children().iter().find_map(|child| child.find(name))
+    }
+
+    /// 节点名称，默认为类型名（不含模块路径），用于追踪与调试输出
+    fn name(&self) -> &str {
+        let path = std::any::type_name::<Self>();
+        let path = path.split('<').next().unwrap_or(path);
+        path.rsplit("::").next().unwrap_or(path)
+    }
+
+    /// 状态机逻辑：tick
+    fn tick(&mut self) -> Status {
+        // 事件驱动执行中，已结束且监听的键未变化的子树沿用上次的结果
+        if runner::reuse_status(self.as_node()) {
+            return self.get_status();
+        }
+
+        let trace = trace::enter(self.name());
+        let status = self.get_status();
+        if status != Status::Running {
+            self.initialize();
+        }
+
+        let new_status = self.update();
+        self.set_status(new_status);
+
+        if new_status != Status::Running {
+            self.terminate();
+        }
+
+        trace::exit(trace, new_status);
+        new_status
+    }
+
+    fn is_success(&self) -> bool {
+        self.get_status() == Status::Success
+    }
+    fn is_failure(&self) -> bool {
+        self.get_status() == Status::Failure
+    }
+    fn is_running(&self) -> bool {
+        self.get_status() == Status::Running
+    }
+    fn is_terminated(&self) -> bool {
+        self.is_success() || self.is_failure()
+    }
+
+    fn reset(&mut self) {
+        self.set_status(Status::Invalid);
+    }
+
+    /// 中断的公共部分：中断全部子节点，本节点正在运行时调用 terminate()，不重置状态
+    ///
+    /// 覆盖 [`halt`](Node::halt) 的节点先调用它，再补充各自的清理。
+    fn interrupt(&mut self) {
+        for child in self.children_mut() {
+            child.halt();
+        }
+        if self.is_running() {
+            self.terminate();
+        }
+    }
+
+    /// 可以覆盖：中断节点，先中断全部子节点，正在运行时调用 terminate()，然后重置为 Invalid
+    fn halt(&mut self) {
+        self.interrupt();
+        self.reset();
+    }
+
+    // ------ 内部状态管理接口 ------
+    fn get_status(&self) -> Status;
+    fn set_status(&mut self, s: Status);
+}
+
+pub trait Composite: Node {
+    fn add_child(&mut self, child: Box<dyn Node>);
+    fn remove_child(&mut self, index: usize) -> Option<Box<dyn Node>>;
+    /// 在 index 处插入子节点，后续子节点依次后移；index 越界时原样返回 child
+    ///
+    /// 若插入位置在当前游标之前（或正运行的子节点处），游标随之后移，保证继续执行原来的子节点。
+    fn insert_child(&mut self, index: usize, child: Box<dyn Node>) -> Result<(), Box<dyn Node>>;
+    fn clear_children(&mut self);
+    fn get_children(&self) -> &Vec<Box<dyn Node>>;
+    /// 可变访问子节点，用于运行时替换或重新配置某个子节点
+    fn get_children_mut(&mut self) -> &mut Vec<Box<dyn Node>>;
+}
+
+/// 一个可复用的 Node 基础实现
+pub struct BaseNode {
+    status: Status,
+    blackboard: Option<BlackboardPtr>,
+}
+
+impl BaseNode {
+    pub fn new() -> Self {
+        Self {
+            status: Status::Invalid,
+            blackboard: None,
+        }
+    }
+}
+
+impl Node for BaseNode {
+    fn get_blackboard(&self) -> Option<BlackboardPtr> {
+        self.blackboard.clone()
+    }
+
+    fn set_blackboard(&mut self, bb: BlackboardPtr) {
+        self.blackboard = Some(bb);
+    }
+
+    fn update(&mut self) -> Status {
+        // 默认什么都不做，直接返回成功
+        Status::Success
+    }
+
+    fn clone_node(&self) -> Box<dyn Node> {
+        Box::new(BaseNode::new())
+    }
+
+    fn get_status(&self) -> Status {
+        self.status
+    }
+
+    fn set_status(&mut self, s: Status) {
+        self.status = s;
+    }
+}
+
+/// 行为树整体操作的错误
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+pub enum TreeError {
+    #[error("expected {expected} cursors for this tree, found {found}")]
+    CursorCount { expected: usize, found: usize },
+    #[error("cursor #{position} is {index} but its node has {len} children")]
+    CursorOutOfRange {
+        position: usize,
+        index: usize,
+        len: usize,
+    },
+    #[error("tree is nested deeper than the limit of {limit} levels")]
+    MaxDepthExceeded { limit: usize },
+}
+
+/// [`BehaviorTree`] 默认允许的最大嵌套深度（根节点深度为 0）
+pub const DEFAULT_MAX_DEPTH: usize = 256;
+
+/// 持有根节点和黑板的行为树
+///
+/// 创建时把黑板设置到整棵树上，之后无需手动调用 `set_blackboard`。
+pub struct BehaviorTree {
+    root: Box<dyn Node>,
+    blackboard: BlackboardPtr,
+    max_depth: usize,
+}
+
+impl BehaviorTree {
+    /// 用新建的空黑板创建行为树
+    pub fn new(root: Box<dyn Node>) -> Self {
+        Self::with_blackboard(root, BlackboardPtr::new())
+    }
+
+    /// 使用已有的黑板创建行为树，可用于多棵树共享黑板
+    pub fn with_blackboard(mut root: Box<dyn Node>, blackboard: BlackboardPtr) -> Self {
+        root.set_blackboard(blackboard.clone());
+        Self {
+            root,
+            blackboard,
+            max_depth: DEFAULT_MAX_DEPTH,
+        }
+    }
+
+    pub fn tick(&mut self) -> Status {
+        self.root.tick()
+    }
+
+    /// 先检查嵌套深度再 tick，树过深时返回 [`TreeError::MaxDepthExceeded`] 而不是栈溢出
+    ///
+    /// 每次调用都会遍历整棵树，树结构不再变化时可先调用一次 [`check_depth`](Self::check_depth)
+    /// 再使用 [`tick`](Self::tick)。
+    pub fn try_tick(&mut self) -> Result<Status, TreeError> {
+        self.check_depth()?;
+        Ok(self.root.tick())
+    }
+
+    pub fn max_depth(&self) -> usize {
+        self.max_depth
+    }
+
+    /// 设置 [`check_depth`](Self::check_depth) 等方法允许的最大嵌套深度，默认为 [`DEFAULT_MAX_DEPTH`]
+    pub fn set_max_depth(&mut self, max_depth: usize) {
+        self.max_depth = max_depth;
+    }
+
+    /// 返回树的嵌套深度，超过 [`max_depth`](Self::max_depth) 时返回错误
+    ///
+    /// 使用显式栈遍历，检查本身不会因树过深而栈溢出。
+    pub fn check_depth(&self) -> Result<usize, TreeError> {
+        let mut deepest = 0;
+        let mut stack = vec![(self.root.as_ref(), 0)];
+        while let Some((node, depth)) = stack.pop() {
+            if depth > self.max_depth {
+                return Err(TreeError::MaxDepthExceeded { limit: self.max_depth });
+            }
+            deepest = deepest.max(depth);
+            stack.extend(node.children().iter().map(|child| (child.as_ref(), depth + 1)));
+        }
+        Ok(deepest)
+    }
+
+    /// 深度检查通过后按先序遍历整棵树，见 [`Node::visit`]
+    pub fn visit(&self, f: &mut dyn FnMut(&dyn Node, usize)) -> Result<(), TreeError> {
+        self.check_depth()?;
+        self.root.visit(f);
+        Ok(())
+    }
+
+    /// 中断整棵树并重置为 Invalid，下次 tick 从头执行；黑板内容保持不变
+    pub fn reset(&mut self) {
+        self.root.halt();
+    }
+
+    pub fn blackboard(&self) -> &BlackboardPtr {
+        &self.blackboard
+    }
+
+    /// 按先序收集树中所有带游标节点（见 [`Node::cursor`]）的游标，用于持久化执行进度
+    pub fn save_cursors(&self) -> Vec<usize> {
+        let mut cursors = Vec::new();
+        self.root.visit(&mut |node, _| cursors.extend(node.cursor()));
+        cursors
+    }
+
+    /// 按 [`save_cursors`](BehaviorTree::save_cursors) 的顺序恢复游标，用于崩溃后从中断处继续执行
+    ///
+    /// 树的结构须与保存时相同。先校验游标数量和每个游标的范围，校验失败时不修改任何节点。
+    pub fn restore_cursors(&mut self, cursors: &[usize]) -> Result<(), TreeError> {
+        let mut lens = Vec::new();
+        self.visit(&mut |node, _| {
+            if node.cursor().is_some() {
+                lens.push(node.children().len());
+            }
+        })?;
+        if lens.len() != cursors.len() {
+            return Err(TreeError::CursorCount {
+                expected: lens.len(),
+                found: cursors.len(),
+            });
+        }
+        for (position, (&index, &len)) in cursors.iter().zip(&lens).enumerate() {
+            // 没有子节点的复合节点游标恒为 0
+            if index >= len.max(1) {
+                return Err(TreeError::CursorOutOfRange { position, index, len });
+            }
+        }
+
+        fn apply(node: &mut dyn Node, cursors: &mut std::slice::Iter<'_, usize>) {
+            if node.cursor().is_some()
+                && let Some(&index) = cursors.next()
+                && node.cursor() != Some(index)
+            {
+                // 范围已校验
+                let _ = node.set_cursor(index);
+            }
+            for child in node.children_mut() {
+                apply(child.as_mut(), cursors);
+            }
+        }
+        apply(self.root.as_mut(), &mut cursors.iter());
+        Ok(())
+    }
+
+    /// 在首次 tick 前校验整棵树声明的端口，详见 [`port::validate`]
+    pub fn validate_ports(&self) -> Result<(), PortError> {
+        port::validate(self.root.as_ref(), &self.blackboard)
+    }
+
+    pub fn root(&self) -> &dyn Node {
+        self.root.as_ref()
+    }
+
+    pub fn root_mut(&mut self) -> &mut dyn Node {
+        self.root.as_mut()
+    }
+}
+
+/// 一个序列节点（依次执行子节点）
+pub struct Sequence {
+    base: BaseNode,
+    children: Vec<Box<dyn Node>>,
+    current: usize,
+    failed: Option<(usize, String)>,
+}
+impl Sequence {
+    pub fn new(children: Vec<Box<dyn Node>>) -> Self {
+        Self {
+            base: BaseNode::new(),
+            children,
+            current: 0,
+            failed: None,
+        }
+    }
+
+    /// 最近一次执行中返回 Failure 的子节点下标和名称，用于排查计划失败的原因
+    ///
+    /// 在下一次进入（initialize）时清空；本次执行未失败时为 None。
+    pub fn last_failed_child(&self) -> Option<(usize, String)> {
+        self.failed.clone()
+    }
+}
+impl Node for Sequence {
+    fn children(&self) -> &[Box<dyn Node>] {
+        &self.children
+    }
+    fn children_mut(&mut self) -> &mut [Box<dyn Node>] {
+        &mut self.children
+    }
+    fn clone_node(&self) -> Box<dyn Node> {
+        let children = self.children.iter().map(|child| child.clone_node()).collect();
+        Box::new(Sequence::new(children))
+    }
+    fn get_blackboard(&self) -> Option<BlackboardPtr> {
+        self.base.get_blackboard()
+    }
+    fn set_blackboard(&mut self, bb: BlackboardPtr) {
+        self.base.set_blackboard(bb.clone());
+        for child in self.children.iter_mut() {
+            child.set_blackboard(bb.clone());
+        }
+    }
+    fn get_status(&self) -> Status {
+        self.base.get_status()
+    }
+    fn set_status(&mut self, s: Status) {
+        self.base.set_status(s);
+    }
+
+    fn initialize(&mut self) {
+        self.current = 0;
+        self.failed = None;
+    }
+
+    fn update(&mut self) -> Status {
+        while self.current < self.children.len() {
+            let status = self.children[self.current].tick();
+            match status {
+                Status::Running => return Status::Running,
+                Status::Failure => {
+                    let name = self.children[self.current].name().to_string();
+                    self.failed = Some((self.current, name));
+                    return Status::Failure;
+                }
+                Status::Success => self.current += 1,
+                // Invalid 表示子节点未正确实现，向上传递，避免反复 tick 同一子节点导致死循环
+                Status::Invalid => return Status::Invalid,
+            }
+        }
+        Status::Success
+    }
+}
+
+// 构建器trait
+impl Composite for Sequence {
+    fn add_child(&mut self, child: Box<dyn Node>) {
+        self.children.push(child);
+    }
+
+    fn remove_child(&mut self, index: usize) -> Option<Box<dyn Node>> {
+        if index < self.children.len() {
+            Some(self.children.remove(index))
+        } else {
+            None
+        }
+    }
+    fn insert_child(&mut self, index: usize, child: Box<dyn Node>) -> Result<(), Box<dyn Node>> {
+        if index > self.children.len() {
+            return Err(child);
+        }
+        if index < self.current || (index == self.current && self.is_running()) {
+            self.current += 1;
+        }
+        self.children.insert(index, child);
+        Ok(())
+    }
+
+    fn clear_children(&mut self) {
+        self.children.clear();
+    }
+
+    fn get_children(&self) -> &Vec<Box<dyn Node>> {
+        &self.children
+    }
+    fn get_children_mut(&mut self) -> &mut Vec<Box<dyn Node>> {
+        &mut self.children
+    }
+}
+
+// selector 节点（依次尝试子节点，直到一个成功）
+pub struct Selector {
+    base: BaseNode,
+    children: Vec<Box<dyn Node>>,
+    current: usize,
+}
+impl Selector {
+    pub fn new(children: Vec<Box<dyn Node>>) -> Self {
+        Self {
+            base: BaseNode::new(),
+            children,
+            current: 0,
+        }
+    }
+}
+impl Node for Selector {
+    fn children(&self) -> &[Box<dyn Node>] {
+        &self.children
+    }
+    fn children_mut(&mut self) -> &mut [Box<dyn Node>] {
+        &mut self.children
+    }
+    fn clone_node(&self) -> Box<dyn Node> {
+        let children = self.children.iter().map(|child| child.clone_node()).collect();
+        Box::new(Selector::new(children))
+    }
+    fn get_blackboard(&self) -> Option<BlackboardPtr> {
+        self.base.get_blackboard()
+    }
+    fn set_blackboard(&mut self, bb: BlackboardPtr) {
+        self.base.set_blackboard(bb.clone());
+        for child in self.children.iter_mut() {
+            child.set_blackboard(bb.clone());
+        }
+    }
+    fn get_status(&self) -> Status {
+        self.base.get_status()
+    }
+    fn set_status(&mut self, s: Status) {
+        self.base.set_status(s);
+    }
+    fn initialize(&mut self) {
+        self.current = 0;
+    }
+    fn update(&mut self) -> Status {
+        while self.current < self.children.len() {
+            let status = self.children[self.current].tick();
+            match status {
+                Status::Running => return Status::Running,
+                Status::Success => return Status::Success,
+                Status::Failure => self.current += 1,
+                Status::Invalid => return Status::Invalid,
+            }
+        }
+        Status::Failure
+    }
+}
+impl Composite for Selector {
+    fn add_child(&mut self, child: Box<dyn Node>) {
+        self.children.push(child);
+    }
+    fn remove_child(&mut self, index: usize) -> Option<Box<dyn Node>> {
+        if index < self.children.len() {
+            Some(self.children.remove(index))
+        } else {
+            None
+        }
+    }
+    fn insert_child(&mut self, index: usize, child: Box<dyn Node>) -> Result<(), Box<dyn Node>> {
+        if index > self.children.len() {
+            return Err(child);
+        }
+        if index < self.current || (index == self.current && self.is_running()) {
+            self.current += 1;
+        }
+        self.children.insert(index, child);
+        Ok(())
+    }
+    fn clear_children(&mut self) {
+        self.children.clear();
+    }
+    fn get_children(&self) -> &Vec<Box<dyn Node>> {
+        &self.children
+    }
+    fn get_children_mut(&mut self) -> &mut Vec<Box<dyn Node>> {
+        &mut self.children
+    }
+}
+
+// 状态顺序节点 （记住上次执行到哪个子节点，下次从该节点继续）
+pub struct StatefulSequence {
+    base: BaseNode,
+    children: Vec<Box<dyn Node>>,
+    current: usize,
+}
+impl StatefulSequence {
+    pub fn new(children: Vec<Box<dyn Node>>) -> Self {
+        Self {
+            base: BaseNode::new(),
+            children,
+            current: 0,
+        }
+    }
+    /// 下一次 tick 将从该下标的子节点开始执行
+    pub fn current_index(&self) -> usize {
+        self.current
+    }
+
+    /// 设置下一次 tick 开始执行的子节点下标，用于恢复执行到一半的树；越界时返回 Err(index)
+    ///
+    /// 只移动游标，不会重置或中断任何子节点的状态。
+    pub fn set_current_index(&mut self, index: usize) -> Result<(), usize> {
+        if index >= self.children.len() {
+            return Err(index);
+        }
+        self.current = index;
+        Ok(())
+    }
+}
+impl Node for StatefulSequence {
+    fn children(&self) -> &[Box<dyn Node>] {
+        &self.children
+    }
+    fn children_mut(&mut self) -> &mut [Box<dyn Node>] {
+        &mut self.children
+    }
+    fn cursor(&self) -> Option<usize> {
+        Some(self.current)
+    }
+    fn set_cursor(&mut self, index: usize) -> Result<(), usize> {
+        self.set_current_index(index)
+    }
+    fn clone_node(&self) -> Box<dyn Node> {
+        let children = self.children.iter().map(|child| child.clone_node()).collect();
+        Box::new(StatefulSequence::new(children))
+    }
+    fn get_blackboard(&self) -> Option<BlackboardPtr> {
+        self.base.get_blackboard()
+    }
+    fn set_blackboard(&mut self, bb: BlackboardPtr) {
+        self.base.set_blackboard(bb.clone());
+        for child in self.children.iter_mut() {
+            child.set_blackboard(bb.clone());
+        }
+    }
+    fn get_status(&self) -> Status {
+        self.base.get_status()
+    }
+    fn set_status(&mut self, s: Status) {
+        self.base.set_status(s);
+    }
+    fn halt(&mut self) {
+        self.interrupt();
+        // 中断后下次从头执行，游标只在正常 tick 之间保留
+        self.current = 0;
+        self.reset();
+    }
+    fn initialize(&mut self) {
+        // 不重置 current
+    }
+    fn update(&mut self) -> Status {
+        while self.current < self.children.len() {
+            let status = self.children[self.current].tick();
+            match status {
+                Status::Running => return Status::Running,
+                Status::Failure => return Status::Failure,
+                Status::Success => self.current += 1,
+                Status::Invalid => return Status::Invalid,
+            }
+        }
+        self.current = 0; // 重置 current
+        Status::Success
+    }
+}
+impl Composite for StatefulSequence {
+    fn add_child(&mut self, child: Box<dyn Node>) {
+        self.children.push(child);
+    }
+    fn remove_child(&mut self, index: usize) -> Option<Box<dyn Node>> {
+        if index < self.children.len() {
+            Some(self.children.remove(index))
+        } else {
+            None
+        }
+    }
+    fn insert_child(&mut self, index: usize, child: Box<dyn Node>) -> Result<(), Box<dyn Node>> {
+        if index > self.children.len() {
+            return Err(child);
+        }
+        if index < self.current || (index == self.current && self.is_running()) {
+            self.current += 1;
+        }
+        self.children.insert(index, child);
+        Ok(())
+    }
+    fn clear_children(&mut self) {
+        self.children.clear();
+    }
+    fn get_children(&self) -> &Vec<Box<dyn Node>> {
+        &self.children
+    }
+    fn get_children_mut(&mut self) -> &mut Vec<Box<dyn Node>> {
+        &mut self.children
+    }
+}
+
+// 状态选择节点 （记住上次执行到哪个子节点，下次从该节点继续）
+pub struct StatefulSelector {
+    base: BaseNode,
+    children: Vec<Box<dyn Node>>,
+    current: usize,
+}
+impl StatefulSelector {
+    pub fn new(children: Vec<Box<dyn Node>>) -> Self {
+        Self {
+            base: BaseNode::new(),
+            children,
+            current: 0,
+        }
+    }
+    /// 下一次 tick 将从该下标的子节点开始执行
+    pub fn current_index(&self) -> usize {
+        self.current
+    }
+
+    /// 设置下一次 tick 开始执行的子节点下标，用于恢复执行到一半的树；越界时返回 Err(index)
+    ///
+    /// 只移动游标，不会重置或中断任何子节点的状态。
+    pub fn set_current_index(&mut self, index: usize) -> Result<(), usize> {
+        if index >= self.children.len() {
+            return Err(index);
+        }
+        self.current = index;
+        Ok(())
+    }
+}
+impl Node for StatefulSelector {
+    fn children(&self) -> &[Box<dyn Node>] {
+        &self.children
+    }
+    fn children_mut(&mut self) -> &mut [Box<dyn Node>] {
+        &mut self.children
+    }
+    fn cursor(&self) -> Option<usize> {
+        Some(self.current)
+    }
+    fn set_cursor(&mut self, index: usize) -> Result<(), usize> {
+        self.set_current_index(index)
+    }
+    fn clone_node(&self) -> Box<dyn Node> {
+        let children = self.children.iter().map(|child| child.clone_node()).collect();
+        Box::new(StatefulSelector::new(children))
+    }
+    fn get_blackboard(&self) -> Option<BlackboardPtr> {
+        self.base.get_blackboard()
+    }
+    fn set_blackboard(&mut self, bb: BlackboardPtr) {
+        self.base.set_blackboard(bb.clone());
+        for child in self.children.iter_mut() {
+            child.set_blackboard(bb.clone());
+        }
+    }
+    fn get_status(&self) -> Status {
+        self.base.get_status()
+    }
+    fn set_status(&mut self, s: Status) {
+        self.base.set_status(s);
+    }
+    fn halt(&mut self) {
+        self.interrupt();
+        // 中断后下次从头执行，游标只在正常 tick 之间保留
+        self.current = 0;
+        self.reset();
+    }
+    fn initialize(&mut self) {
+        // 不重置 current
+    }
+    fn update(&mut self) -> Status {
+        while self.current < self.children.len() {
+            let status = self.children[self.current].tick();
+            match status {
+                Status::Running => return Status::Running,
+                Status::Success => return Status::Success,
+                Status::Failure => self.current += 1,
+                Status::Invalid => return Status::Invalid,
+            }
+        }
+        self.current = 0; // 重置 current
+        Status::Failure
+    }
+}
+impl Composite for StatefulSelector {
+    fn add_child(&mut self, child: Box<dyn Node>) {
+        self.children.push(child);
+    }
+    fn remove_child(&mut self, index: usize) -> Option<Box<dyn Node>> {
+        if index < self.children.len() {
+            Some(self.children.remove(index))
+        } else {
+            None
+        }
+    }
+    fn insert_child(&mut self, index: usize, child: Box<dyn Node>) -> Result<(), Box<dyn Node>> {
+        if index > self.children.len() {
+            return Err(child);
+        }
+        if index < self.current || (index == self.current && self.is_running()) {
+            self.current += 1;
+        }
+        self.children.insert(index, child);
+        Ok(())
+    }
+    fn clear_children(&mut self) {
+        self.children.clear();
+    }
+    fn get_children(&self) -> &Vec<Box<dyn Node>> {
+        &self.children
+    }
+    fn get_children_mut(&mut self) -> &mut Vec<Box<dyn Node>> {
+        &mut self.children
+    }
+}
+
+// 随机选择节点（每次进入时均匀随机选中一个子节点，并在本次运行期间只执行它）
+// 选中的子节点失败时不会尝试其他子节点
+pub struct RandomSelector {
+    base: BaseNode,
+    children: Vec<Box<dyn Node>>,
+    current: usize,
+    rng: SplitMix64,
+    // with_seed 设置的种子，clone_node 据此构造副本的随机数生成器
+    seed: Option<u64>,
+}
+impl RandomSelector {
+    pub fn new(children: Vec<Box<dyn Node>>) -> Self {
+        Self {
+            base: BaseNode::new(),
+            children,
+            current: 0,
+            rng: SplitMix64::from_entropy(),
+            seed: None,
+        }
+    }
+    /// 使用固定种子，便于测试和仿真复现
+    ///
+    /// `clone_node` 得到的副本使用同一种子，从头产生与新建节点相同的选择序列。
+    pub fn with_seed(self, seed: u64) -> Self {
+        Self {
+            rng: SplitMix64::new(seed),
+            seed: Some(seed),
+            ..self
+        }
+    }
+    /// 本次运行选中的子节点下标
+    pub fn selected(&self) -> usize {
+        self.current
+    }
+}
+impl Node for RandomSelector {
+    fn children(&self) -> &[Box<dyn Node>] {
+        &self.children
+    }
+    fn children_mut(&mut self) -> &mut [Box<dyn Node>] {
+        &mut self.children
+    }
+    fn clone_node(&self) -> Box<dyn Node> {
+        let children = self.children.iter().map(|child| child.clone_node()).collect();
+        let copy = RandomSelector::new(children);
+        Box::new(match self.seed {
+            Some(seed) => copy.with_seed(seed),
+            None => copy,
+        })
+    }
+    fn get_blackboard(&self) -> Option<BlackboardPtr> {
+        self.base.get_blackboard()
+    }
+    fn set_blackboard(&mut self, bb: BlackboardPtr) {
+        self.base.set_blackboard(bb.clone());
+        for child in self.children.iter_mut() {
+            child.set_blackboard(bb.clone());
+        }
+    }
+    fn get_status(&self) -> Status {
+        self.base.get_status()
+    }
+    fn set_status(&mut self, s: Status) {
+        self.base.set_status(s);
+    }
+    fn initialize(&mut self) {
+        if !self.children.is_empty() {
+            self.current = self.rng.below(self.children.len());
+        }
+    }
+    fn update(&mut self) -> Status {
+        match self.children.get_mut(self.current) {
+            Some(child) => child.tick(),
+            None => Status::Failure,
+        }
+    }
+}
+impl Composite for RandomSelector {
+    fn add_child(&mut self, child: Box<dyn Node>) {
+        self.children.push(child);
+    }
+    fn remove_child(&mut self, index: usize) -> Option<Box<dyn Node>> {
+        if index < self.children.len() {
+            Some(self.children.remove(index))
+        } else {
+            None
+        }
+    }
+    fn insert_child(&mut self, index: usize, child: Box<dyn Node>) -> Result<(), Box<dyn Node>> {
+        if index > self.children.len() {
+            return Err(child);
+        }
+        if index < self.current || (index == self.current && self.is_running()) {
+            self.current += 1;
+        }
+        self.children.insert(index, child);
+        Ok(())
+    }
+    fn clear_children(&mut self) {
+        self.children.clear();
+    }
+    fn get_children(&self) -> &Vec<Box<dyn Node>> {
+        &self.children
+    }
+    fn get_children_mut(&mut self) -> &mut Vec<Box<dyn Node>> {
+        &mut self.children
+    }
+}
+
+/// 效用评分函数，根据黑板内容给子节点打分
+pub type Scorer = Box<dyn Fn(&BlackboardPtr) -> f32>;
+type SharedScorer = Rc<dyn Fn(&BlackboardPtr) -> f32>;
+
+// 效用选择节点（每次 update 时按评分从高到低排列子节点，再按 Selector 语义依次尝试）
+// 分数相同按原始顺序；NaN 视为最低分；未设置黑板时所有子节点同分。
+// 每次 tick 重新评分，若之前运行中的子节点不再被执行则将其中断。
+pub struct UtilitySelector {
+    base: BaseNode,
+    children: Vec<Box<dyn Node>>,
+    scorers: Vec<SharedScorer>,
+    running: Option<usize>,
+}
+impl UtilitySelector {
+    pub fn new(children: Vec<(Box<dyn Node>, Scorer)>) -> Self {
+        let (children, scorers) = children
+            .into_iter()
+            .map(|(child, scorer)| (child, Rc::from(scorer)))
+            .unzip();
+        Self {
+            base: BaseNode::new(),
+            children,
+            scorers,
+            running: None,
+        }
+    }
+    /// 添加带评分函数的子节点；`Composite::add_child` 添加的子节点评分恒为 0
+    pub fn add_scored_child(&mut self, child: Box<dyn Node>, scorer: Scorer) {
+        self.children.push(child);
+        self.scorers.push(Rc::from(scorer));
+    }
+    // 按评分从高到低排列的子节点下标
+    fn ranked(&self) -> Vec<usize> {
+        let scores: Vec<f32> = match self.get_blackboard() {
+            Some(bb) => self
+                .scorers
+                .iter()
+                .map(|scorer| scorer(&bb))
+                .map(|score| if score.is_nan() { f32::NEG_INFINITY } else { score })
+                .collect(),
+            None => vec![0.0; self.children.len()],
+        };
+        let mut order: Vec<usize> = (0..self.children.len()).collect();
+        // sort_by 是稳定排序，同分保持原始顺序
+        order.sort_by(|&a, &b| scores[b].total_cmp(&scores[a]));
+        order
+    }
+}
+impl Node for UtilitySelector {
+    fn children(&self) -> &[Box<dyn Node>] {
+        &self.children
+    }
+    fn children_mut(&mut self) -> &mut [Box<dyn Node>] {
+        &mut self.children
+    }
+    fn clone_node(&self) -> Box<dyn Node> {
+        Box::new(UtilitySelector {
+            base: BaseNode::new(),
+            children: self.children.iter().map(|child| child.clone_node()).collect(),
+            scorers: self.scorers.clone(),
+            running: None,
+        })
+    }
+    fn get_blackboard(&self) -> Option<BlackboardPtr> {
+        self.base.get_blackboard()
+    }
+    fn set_blackboard(&mut self, bb: BlackboardPtr) {
+        self.base.set_blackboard(bb.clone());
+        for child in self.children.iter_mut() {
+            child.set_blackboard(bb.clone());
+        }
+    }
+    fn get_status(&self) -> Status {
+        self.base.get_status()
+    }
+    fn set_status(&mut self, s: Status) {
+        self.base.set_status(s);
+    }
+    fn halt(&mut self) {
+        self.interrupt();
+        self.running = None;
+        self.reset();
+    }
+    fn initialize(&mut self) {
+        self.running = None;
+    }
+    fn update(&mut self) -> Status {
+        for index in self.ranked() {
+            let status = self.children[index].tick();
+            if status == Status::Failure {
+                continue;
+            }
+            // 之前运行中的子节点被更高分的子节点抢占
+            if let Some(prev) = self.running.take()
+                && prev != index
+            {
+                self.children[prev].halt();
+            }
+            if status == Status::Running {
+                self.running = Some(index);
+            }
+            return status;
+        }
+        if let Some(prev) = self.running.take() {
+            self.children[prev].halt();
+        }
+        Status::Failure
+    }
+}
+impl Composite for UtilitySelector {
+    fn add_child(&mut self, child: Box<dyn Node>) {
+        self.add_scored_child(child, Box::new(|_| 0.0));
+    }
+    fn remove_child(&mut self, index: usize) -> Option<Box<dyn Node>> {
+        if index < self.children.len() {
+            self.scorers.remove(index);
+            self.running = None;
+            Some(self.children.remove(index))
+        } else {
+            None
+        }
+    }
+    fn insert_child(&mut self, index: usize, child: Box<dyn Node>) -> Result<(), Box<dyn Node>> {
+        if index > self.children.len() {
+            return Err(child);
+        }
+        if let Some(running) = self.running.as_mut()
+            && index <= *running
+        {
+            *running += 1;
+        }
+        self.children.insert(index, child);
+        self.scorers.insert(index, Rc::new(|_| 0.0));
+        Ok(())
+    }
+    fn clear_children(&mut self) {
+        self.children.clear();
+        self.scorers.clear();
+        self.running = None;
+    }
+    fn get_children(&self) -> &Vec<Box<dyn Node>> {
+        &self.children
+    }
+    fn get_children_mut(&mut self) -> &mut Vec<Box<dyn Node>> {
+        &mut self.children
+    }
+}
+
+/// 并行等待节点：每次 tick 所有未结束的子节点，全部结束后才结束
+///
+/// 有子节点仍在运行时返回 Running；全部结束后，全部成功返回 Success，否则返回 Failure。
+/// 与 Sequence 不同，某个子节点失败不会提前结束，适合汇总多个相互独立的子结果。
+/// 已结束的子节点在下次进入（initialize）之前不会再被 tick。
+pub struct ParallelAll {
+    base: BaseNode,
+    children: Vec<Box<dyn Node>>,
+}
+impl ParallelAll {
+    pub fn new(children: Vec<Box<dyn Node>>) -> Self {
+        Self {
+            base: BaseNode::new(),
+            children,
+        }
+    }
+}
+impl Node for ParallelAll {
+    fn children(&self) -> &[Box<dyn Node>] {
+        &self.children
+    }
+    fn children_mut(&mut self) -> &mut [Box<dyn Node>] {
+        &mut self.children
+    }
+    fn clone_node(&self) -> Box<dyn Node> {
+        let children = self.children.iter().map(|child| child.clone_node()).collect();
+        Box::new(ParallelAll::new(children))
+    }
+    fn get_blackboard(&self) -> Option<BlackboardPtr> {
+        self.base.get_blackboard()
+    }
+    fn set_blackboard(&mut self, bb: BlackboardPtr) {
+        self.base.set_blackboard(bb.clone());
+        for child in self.children.iter_mut() {
+            child.set_blackboard(bb.clone());
+        }
+    }
+    fn get_status(&self) -> Status {
+        self.base.get_status()
+    }
+    fn set_status(&mut self, s: Status) {
+        self.base.set_status(s);
+    }
+    fn initialize(&mut self) {
+        // 清除上一轮的结果，使所有子节点重新执行
+        for child in self.children.iter_mut() {
+            child.reset();
+        }
+    }
+    fn update(&mut self) -> Status {
+        let mut running = false;
+        for child in self.children.iter_mut() {
+            if child.is_terminated() {
+                continue;
+            }
+            match child.tick() {
+                Status::Running => running = true,
+                Status::Success | Status::Failure => {}
+                Status::Invalid => {
+                    for child in self.children.iter_mut() {
+                        child.halt();
+                    }
+                    return Status::Invalid;
+                }
+            }
+        }
+        if running {
+            Status::Running
+        } else if self.children.iter().all(|child| child.is_success()) {
+            Status::Success
+        } else {
+            Status::Failure
+        }
+    }
+}
+impl Composite for ParallelAll {
+    fn add_child(&mut self, child: Box<dyn Node>) {
+        self.children.push(child);
+    }
+    fn remove_child(&mut self, index: usize) -> Option<Box<dyn Node>> {
+        if index < self.children.len() {
+            Some(self.children.remove(index))
+        } else {
+            None
+        }
+    }
+    fn insert_child(&mut self, index: usize, child: Box<dyn Node>) -> Result<(), Box<dyn Node>> {
+        if index > self.children.len() {
+            return Err(child);
+        }
+        self.children.insert(index, child);
+        Ok(())
+    }
+    fn clear_children(&mut self) {
+        self.children.clear();
+    }
+    fn get_children(&self) -> &Vec<Box<dyn Node>> {
+        &self.children
+    }
+    fn get_children_mut(&mut self) -> &mut Vec<Box<dyn Node>> {
+        &mut self.children
+    }
+}
+
+/// 分支选择节点：进入时从黑板读取 key 对应的 usize 下标，只执行该下标的子节点
+///
+/// 返回被选中子节点的状态；key 不存在、类型不是 usize 或下标越界时返回 Failure。
+/// 下标只在进入（initialize）时读取，子节点 Running 期间修改 key 不会切换分支。
+pub struct Switch {
+    base: BaseNode,
+    key: String,
+    children: Vec<Box<dyn Node>>,
+    selected: Option<usize>,
+}
+impl Switch {
+    pub fn new(key: &str, children: Vec<Box<dyn Node>>) -> Self {
+        Self {
+            base: BaseNode::new(),
+            key: key.to_string(),
+            children,
+            selected: None,
+        }
+    }
+
+    /// 本次执行选中的子节点下标，未选中任何子节点时为 None
+    pub fn selected_index(&self) -> Option<usize> {
+        self.selected
+    }
+}
+impl Node for Switch {
+    fn children(&self) -> &[Box<dyn Node>] {
+        &self.children
+    }
+    fn children_mut(&mut self) -> &mut [Box<dyn Node>] {
+        &mut self.children
+    }
+    fn clone_node(&self) -> Box<dyn Node> {
+        let children = self.children.iter().map(|child| child.clone_node()).collect();
+        Box::new(Switch::new(&self.key, children))
+    }
+    fn get_blackboard(&self) -> Option<BlackboardPtr> {
+        self.base.get_blackboard()
+    }
+    fn set_blackboard(&mut self, bb: BlackboardPtr) {
+        self.base.set_blackboard(bb.clone());
+        for child in self.children.iter_mut() {
+            child.set_blackboard(bb.clone());
+        }
+    }
+    fn get_status(&self) -> Status {
+        self.base.get_status()
+    }
+    fn set_status(&mut self, s: Status) {
+        self.base.set_status(s);
+    }
+    fn initialize(&mut self) {
+        self.selected = self
+            .get_blackboard()
+            .and_then(|bb| bb.get::<usize>(&self.key).map(|index| *index))
+            .filter(|&index| index < self.children.len());
+    }
+    fn update(&mut self) -> Status {
+        match self.selected {
+            Some(index) => self.children[index].tick(),
+            None => Status::Failure,
+        }
+    }
+}
+impl Composite for Switch {
+    fn add_child(&mut self, child: Box<dyn Node>) {
+        self.children.push(child);
+    }
+    fn remove_child(&mut self, index: usize) -> Option<Box<dyn Node>> {
+        if index >= self.children.len() {
+            return None;
+        }
+        // 移除正在执行的分支时，下次 tick 不再执行任何子节点
+        self.selected = match self.selected {
+            Some(selected) if selected == index => None,
+            Some(selected) if selected > index => Some(selected - 1),
+            selected => selected,
+        };
+        Some(self.children.remove(index))
+    }
+    fn insert_child(&mut self, index: usize, child: Box<dyn Node>) -> Result<(), Box<dyn Node>> {
+        if index > self.children.len() {
+            return Err(child);
+        }
+        if let Some(selected) = self.selected.as_mut()
+            && index <= *selected
+        {
+            *selected += 1;
+        }
+        self.children.insert(index, child);
+        Ok(())
+    }
+    fn clear_children(&mut self) {
+        self.children.clear();
+        self.selected = None;
+    }
+    fn get_children(&self) -> &Vec<Box<dyn Node>> {
+        &self.children
+    }
+    fn get_children_mut(&mut self) -> &mut Vec<Box<dyn Node>> {
+        &mut self.children
+    }
+}
+
+/// 带记忆的选择节点：子节点失败后被标记为“粘滞失败”，之后的执行中直接跳过，不再 tick
+///
+/// 适用于长期运行的树中永久不可用的分支（如不存在的硬件）。粘滞标记在 halt()
+/// （包括 [`BehaviorTree::reset`]）后保留，只有 [`clear_sticky`](StickySelector::clear_sticky)
+/// 或对本节点调用 `reset()` 才会清除。所有子节点都粘滞失败时直接返回 Failure。
+pub struct StickySelector {
+    base: BaseNode,
+    children: Vec<Box<dyn Node>>,
+    current: usize,
+    // 与 children 按下标对应，长度可能短于 children（缺省视为未标记）
+    sticky: Vec<bool>,
+}
+impl StickySelector {
+    pub fn new(children: Vec<Box<dyn Node>>) -> Self {
+        Self {
+            base: BaseNode::new(),
+            children,
+            current: 0,
+            sticky: Vec::new(),
+        }
+    }
+
+    /// 下标为 index 的子节点是否已被标记为粘滞失败
+    pub fn is_sticky(&self, index: usize) -> bool {
+        self.sticky.get(index).copied().unwrap_or(false)
+    }
+
+    /// 清除所有粘滞标记，之后的执行重新尝试全部子节点
+    pub fn clear_sticky(&mut self) {
+        self.sticky.clear();
+    }
+
+    fn mark_sticky(&mut self, index: usize) {
+        if self.sticky.len() <= index {
+            self.sticky.resize(index + 1, false);
+        }
+        self.sticky[index] = true;
+    }
+}
+impl Node for StickySelector {
+    fn children(&self) -> &[Box<dyn Node>] {
+        &self.children
+    }
+    fn children_mut(&mut self) -> &mut [Box<dyn Node>] {
+        &mut self.children
+    }
+    fn clone_node(&self) -> Box<dyn Node> {
+        let children = self.children.iter().map(|child| child.clone_node()).collect();
+        Box::new(StickySelector::new(children))
+    }
+    fn get_blackboard(&self) -> Option<BlackboardPtr> {
+        self.base.get_blackboard()
+    }
+    fn set_blackboard(&mut self, bb: BlackboardPtr) {
+        self.base.set_blackboard(bb.clone());
+        for child in self.children.iter_mut() {
+            child.set_blackboard(bb.clone());
+        }
+    }
+    fn get_status(&self) -> Status {
+        self.base.get_status()
+    }
+    fn set_status(&mut self, s: Status) {
+        self.base.set_status(s);
+    }
+    fn reset(&mut self) {
+        self.clear_sticky();
+        self.set_status(Status::Invalid);
+    }
+    // 与默认 halt 不同，不调用 reset()，以保留粘滞标记
+    fn halt(&mut self) {
+        self.interrupt();
+        self.set_status(Status::Invalid);
+    }
+    fn initialize(&mut self) {
+        self.current = 0;
+    }
+    fn update(&mut self) -> Status {
+        while self.current < self.children.len() {
+            if self.is_sticky(self.current) {
+                self.current += 1;
+                continue;
+            }
+            match self.children[self.current].tick() {
+                Status::Running => return Status::Running,
+                Status::Success => return Status::Success,
+                Status::Failure => {
+                    self.mark_sticky(self.current);
+                    self.current += 1;
+                }
+                Status::Invalid => return Status::Invalid,
+            }
+        }
+        Status::Failure
+    }
+}
+impl Composite for StickySelector {
+    fn add_child(&mut self, child: Box<dyn Node>) {
+        self.children.push(child);
+    }
+    fn remove_child(&mut self, index: usize) -> Option<Box<dyn Node>> {
+        if index >= self.children.len() {
+            return None;
+        }
+        if index < self.sticky.len() {
+            self.sticky.remove(index);
+        }
+        Some(self.children.remove(index))
+    }
+    fn insert_child(&mut self, index: usize, child: Box<dyn Node>) -> Result<(), Box<dyn Node>> {
+        if index > self.children.len() {
+            return Err(child);
+        }
+        if index < self.current || (index == self.current && self.is_running()) {
+            self.current += 1;
+        }
+        if index < self.sticky.len() {
+            self.sticky.insert(index, false);
+        }
+        self.children.insert(index, child);
+        Ok(())
+    }
+    fn clear_children(&mut self) {
+        self.children.clear();
+        self.sticky.clear();
+    }
+    fn get_children(&self) -> &Vec<Box<dyn Node>> {
+        &self.children
+    }
+    fn get_children_mut(&mut self) -> &mut Vec<Box<dyn Node>> {
+        &mut self.children
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::decorator;
+    use crate::testing::ScriptNode;
+
+    // 每次 tick 把黑板中的 count 加一
+    struct Increment {
+        base: BaseNode,
+    }
+
+    impl Node for Increment {
+        fn get_blackboard(&self) -> Option<BlackboardPtr> {
+            self.base.get_blackboard()
+        }
+        fn set_blackboard(&mut self, bb: BlackboardPtr) {
+            self.base.set_blackboard(bb);
+        }
+        fn get_status(&self) -> Status {
+            self.base.get_status()
+        }
+        fn set_status(&mut self, s: Status) {
+            self.base.set_status(s);
+        }
+        fn update(&mut self) -> Status {
+            *self
+                .blackboard_or_default()
+                .entry_or_insert_with("count", || 0i64) += 1;
+            Status::Success
+        }
+    }
+
+    #[test]
+    fn leaf_ticks_without_blackboard() {
+        let mut leaf = Increment { base: BaseNode::new() };
+        assert!(leaf.get_blackboard().is_none());
+
+        assert_eq!(leaf.tick(), Status::Success);
+        assert_eq!(leaf.tick(), Status::Success);
+        // 孤立黑板保存在节点上，多次 tick 共用
+        assert_eq!(leaf.get_blackboard().unwrap().get_i64("count"), Some(2));
+    }
+
+    #[test]
+    fn behavior_tree_wires_blackboard() {
+        let root = Sequence::new(vec![
+            Box::new(Increment { base: BaseNode::new() }),
+            Box::new(ScriptNode::new(&[Status::Running, Status::Success])),
+            Box::new(Increment { base: BaseNode::new() }),
+        ]);
+        let mut tree = BehaviorTree::new(Box::new(root));
+
+        assert_eq!(tree.tick(), Status::Running);
+        assert_eq!(tree.blackboard().get_i64("count"), Some(1));
+        assert_eq!(tree.tick(), Status::Success);
+        assert_eq!(tree.blackboard().get_i64("count"), Some(2));
+
+        // 重置后从头执行，黑板内容保留
+        tree.reset();
+        assert_eq!(tree.root().get_status(), Status::Invalid);
+        assert_eq!(tree.tick(), Status::Success);
+        assert_eq!(tree.blackboard().get_i64("count"), Some(4));
+    }
+
+    #[test]
+    fn reset_restarts_stateful_composites() {
+        let first = ScriptNode::new(&[Status::Success]);
+        let probe = first.probe();
+        let root = StatefulSequence::new(vec![
+            Box::new(first),
+            Box::new(ScriptNode::new(&[Status::Running])),
+        ]);
+        let mut tree = BehaviorTree::new(Box::new(root));
+        assert_eq!(tree.tick(), Status::Running);
+        assert_eq!(tree.save_cursors(), [1]);
+
+        tree.reset();
+        assert_eq!(tree.save_cursors(), [0]);
+        assert_eq!(tree.tick(), Status::Running);
+        assert_eq!(probe.updates(), 2);
+
+        let first = ScriptNode::new(&[Status::Failure]);
+        let probe = first.probe();
+        let root = StatefulSelector::new(vec![
+            Box::new(first),
+            Box::new(ScriptNode::new(&[Status::Running])),
+        ]);
+        let mut tree = BehaviorTree::new(Box::new(root));
+        assert_eq!(tree.tick(), Status::Running);
+        tree.reset();
+        assert_eq!(tree.tick(), Status::Running);
+        assert_eq!(probe.updates(), 2);
+    }
+
+    #[test]
+    fn composites_propagate_invalid_child() {
+        let invalid = || -> Vec<Box<dyn Node>> {
+            vec![
+                Box::new(ScriptNode::new(&[Status::Invalid])),
+                Box::new(ScriptNode::new(&[Status::Success])),
+            ]
+        };
+        let mut composites: Vec<Box<dyn Node>> = vec![
+            Box::new(Sequence::new(invalid())),
+            Box::new(Selector::new(invalid())),
+            Box::new(StatefulSequence::new(invalid())),
+            Box::new(StatefulSelector::new(invalid())),
+        ];
+        for composite in composites.iter_mut() {
+            assert_eq!(composite.tick(), Status::Invalid, "{}", composite.name());
+            assert_eq!(composite.tick(), Status::Invalid, "{}", composite.name());
+        }
+    }
+
+    #[test]
+    fn parallel_all_waits_for_every_child() {
+        let fast = ScriptNode::new(&[Status::Success]);
+        let medium = ScriptNode::new(&[Status::Running, Status::Success]);
+        let slow = ScriptNode::new(&[Status::Running, Status::Running, Status::Failure]);
+        let (fast_probe, medium_probe) = (fast.probe(), medium.probe());
+        let mut node = ParallelAll::new(vec![Box::new(fast), Box::new(medium), Box::new(slow)]);
+
+        assert_eq!(node.tick(), Status::Running);
+        assert_eq!(node.tick(), Status::Running);
+        assert_eq!(node.tick(), Status::Failure);
+        // 已结束的子节点不再被 tick
+        assert_eq!(fast_probe.updates(), 1);
+        assert_eq!(medium_probe.updates(), 2);
+
+        // 重新进入后所有子节点重新执行
+        node.tick();
+        assert_eq!(fast_probe.updates(), 2);
+    }
+
+    #[test]
+    fn cloned_templates_do_not_share_state() {
+        let template = Sequence::new(vec![
+            Box::new(ScriptNode::new(&[Status::Running, Status::Success])),
+            Box::new(ScriptNode::new(&[Status::Success])),
+        ]);
+        let mut a = template.clone_node();
+        let mut b = template.clone_node();
+
+        assert_eq!(a.tick(), Status::Running);
+        assert_eq!(a.tick(), Status::Success);
+        assert_eq!(b.get_status(), Status::Invalid);
+        assert_eq!(template.get_status(), Status::Invalid);
+
+        // b 的叶子脚本从头开始
+        assert_eq!(b.tick(), Status::Running);
+    }
+
+    #[test]
+    fn visit_reports_every_node_with_depth() {
+        let tree = Sequence::new(vec![
+            Box::new(Selector::new(vec![
+                Box::new(ScriptNode::new(&[Status::Failure])),
+                Box::new(ScriptNode::new(&[Status::Success])),
+            ])),
+            Box::new(decorator::MaxTicks::new(
+                Box::new(ScriptNode::new(&[Status::Running])),
+                3,
+                Status::Failure,
+            )),
+            Box::new(ScriptNode::new(&[Status::Success])),
+        ]);
+
+        let mut count = 0;
+        let mut max_depth = 0;
+        tree.visit(&mut |_, depth| {
+            count += 1;
+            max_depth = max_depth.max(depth);
+        });
+        assert_eq!(count, 7);
+        assert_eq!(max_depth, 2);
+
+        assert_eq!(tree.find("MaxTicks").unwrap().children().len(), 1);
+        assert!(tree.find("Parallel").is_none());
+    }
+
+    #[test]
+    fn random_selector_commits_to_seeded_choice() {
+        let leaves: Vec<_> = (0..4)
+            .map(|_| ScriptNode::new(&[Status::Running, Status::Failure]))
+            .collect();
+        let probes: Vec<_> = leaves.iter().map(|leaf| leaf.probe()).collect();
+        let children = leaves
+            .into_iter()
+            .map(|leaf| Box::new(leaf) as Box<dyn Node>)
+            .collect();
+        let mut node = RandomSelector::new(children).with_seed(7);
+
+        assert_eq!(node.tick(), Status::Running);
+        assert_eq!(node.selected(), 3);
+        // 选中的子节点失败后不会退回到其他子节点
+        assert_eq!(node.tick(), Status::Failure);
+        let updates: Vec<_> = probes.iter().map(|probe| probe.updates()).collect();
+        assert_eq!(updates, vec![0, 0, 0, 2]);
+    }
+
+    #[test]
+    fn random_selector_clones_keep_seed() {
+        use crate::decorator::{OnResult, ResultCallback};
+
+        // 每个子节点成功时把自己的下标写入黑板
+        let children = (0..4usize)
+            .map(|index| {
+                let record: ResultCallback = Box::new(move |_, bb| bb.set("picked", index));
+                let leaf = Box::new(ScriptNode::new(&[Status::Success]));
+                Box::new(OnResult::new(leaf, Some(record), None)) as Box<dyn Node>
+            })
+            .collect();
+        let mut template = RandomSelector::new(children).with_seed(7);
+        // 模板自身已推进过的随机状态不影响副本
+        template.tick();
+
+        let picks = |mut node: Box<dyn Node>| -> Vec<usize> {
+            let bb = BlackboardPtr::new();
+            node.set_blackboard(bb.clone());
+            (0..8)
+                .map(|_| {
+                    node.tick();
+                    *bb.get::<usize>("picked").unwrap()
+                })
+                .collect()
+        };
+        let expected = picks(template.clone_node());
+        assert_eq!(expected[0], 3);
+        assert_eq!(picks(template.clone_node()), expected);
+    }
+
+    #[test]
+    fn replace_child_through_mutable_accessor() {
+        let mut root = Selector::new(vec![
+            Box::new(ScriptNode::new(&[Status::Failure])),
+            Box::new(ScriptNode::new(&[Status::Failure])),
+        ]);
+        assert_eq!(root.tick(), Status::Failure);
+
+        root.get_children_mut()[1] = Box::new(ScriptNode::new(&[Status::Success]));
+        assert_eq!(root.tick(), Status::Success);
+    }
+
+    #[test]
+    fn insert_before_cursor_keeps_running_child() {
+        let first = ScriptNode::new(&[Status::Success]);
+        let second = ScriptNode::new(&[Status::Running, Status::Success]);
+        let second_probe = second.probe();
+        let mut root = StatefulSequence::new(vec![Box::new(first), Box::new(second)]);
+        assert_eq!(root.tick(), Status::Running);
+
+        let inserted = ScriptNode::new(&[Status::Success]);
+        let inserted_probe = inserted.probe();
+        assert!(root.insert_child(0, Box::new(inserted)).is_ok());
+        assert!(root.insert_child(9, Box::new(BaseNode::new())).is_err());
+
+        // 本次运行从原来的子节点继续，插入的节点留到下一次运行
+        assert_eq!(root.tick(), Status::Success);
+        assert_eq!(second_probe.updates(), 2);
+        assert_eq!(inserted_probe.updates(), 0);
+        assert_eq!(root.tick(), Status::Success);
+        assert_eq!(inserted_probe.updates(), 1);
+    }
+
+    #[test]
+    fn utility_selector_tries_highest_score_first() {
+        let first = ScriptNode::new(&[Status::Success]);
+        let first_probe = first.probe();
+        let second = ScriptNode::new(&[Status::Success]);
+        let second_probe = second.probe();
+        let score = |key: &'static str| -> Scorer {
+            Box::new(move |bb| bb.get::<f32>(key).map_or(0.0, |v| *v))
+        };
+        let mut root = UtilitySelector::new(vec![
+            (Box::new(first), score("first")),
+            (Box::new(second), score("second")),
+        ]);
+        let bb = BlackboardPtr::new();
+        bb.borrow_mut().insert("first".to_string(), Box::new(1.0f32));
+        bb.borrow_mut().insert("second".to_string(), Box::new(5.0f32));
+        root.set_blackboard(bb.clone());
+
+        assert_eq!(root.tick(), Status::Success);
+        assert_eq!(first_probe.updates(), 0);
+        assert_eq!(second_probe.updates(), 1);
+
+        // 同分时按原始顺序
+        bb.borrow_mut().insert("second".to_string(), Box::new(1.0f32));
+        assert_eq!(root.tick(), Status::Success);
+        assert_eq!(first_probe.updates(), 1);
+    }
+
+    #[test]
+    fn sequence_records_failed_child() {
+        let mut seq = Sequence::new(vec![
+            Box::new(ScriptNode::new(&[Status::Success])),
+            Box::new(ScriptNode::new(&[Status::Failure, Status::Success])),
+            Box::new(ScriptNode::new(&[Status::Success])),
+        ]);
+
+        assert_eq!(seq.tick(), Status::Failure);
+        assert_eq!(seq.last_failed_child(), Some((1, "ScriptNode".to_string())));
+
+        assert_eq!(seq.tick(), Status::Success);
+        assert_eq!(seq.last_failed_child(), None);
+    }
+
+    #[test]
+    fn stateful_sequence_resumes_from_set_index() {
+        let first = ScriptNode::new(&[Status::Success]);
+        let first_probe = first.probe();
+        let second = ScriptNode::new(&[Status::Success]);
+        let second_probe = second.probe();
+        let mut seq = StatefulSequence::new(vec![Box::new(first), Box::new(second)]);
+
+        assert_eq!(seq.set_current_index(2), Err(2));
+        assert_eq!(seq.set_current_index(1), Ok(()));
+        assert_eq!(seq.current_index(), 1);
+
+        assert_eq!(seq.tick(), Status::Success);
+        assert_eq!(first_probe.updates(), 0);
+        assert_eq!(second_probe.updates(), 1);
+        assert_eq!(seq.current_index(), 0);
+    }
+
+    #[test]
+    fn switch_ticks_only_selected_child() {
+        let first = ScriptNode::new(&[Status::Success]);
+        let second = ScriptNode::new(&[Status::Running, Status::Failure]);
+        let (first_probe, second_probe) = (first.probe(), second.probe());
+        let mut tree = BehaviorTree::new(Box::new(Switch::new(
+            "mode",
+            vec![Box::new(first), Box::new(second)],
+        )));
+
+        tree.blackboard().set("mode", 1_usize);
+        assert_eq!(tree.tick(), Status::Running);
+        // Running 期间不重新读取 key
+        tree.blackboard().set("mode", 0_usize);
+        assert_eq!(tree.tick(), Status::Failure);
+        assert_eq!(first_probe.updates(), 0);
+        assert_eq!(second_probe.updates(), 2);
+
+        tree.blackboard().set("mode", 5_usize);
+        assert_eq!(tree.tick(), Status::Failure);
+        assert_eq!(second_probe.updates(), 2);
+    }
+
+    #[test]
+    fn sticky_selector_skips_failed_child() {
+        let flaky = ScriptNode::new(&[Status::Failure, Status::Success]);
+        let flaky_probe = flaky.probe();
+        let fallback = ScriptNode::new(&[Status::Success]);
+        let fallback_probe = fallback.probe();
+        let mut root = StickySelector::new(vec![Box::new(flaky), Box::new(fallback)]);
+
+        assert_eq!(root.tick(), Status::Success);
+        assert!(root.is_sticky(0));
+        // 第二次执行不再 tick 失败过的子节点，halt 也不清除标记
+        root.halt();
+        assert_eq!(root.tick(), Status::Success);
+        assert_eq!(flaky_probe.updates(), 1);
+        assert_eq!(fallback_probe.updates(), 2);
+
+        root.clear_sticky();
+        assert_eq!(root.tick(), Status::Success);
+        assert_eq!(flaky_probe.updates(), 2);
+        assert_eq!(fallback_probe.updates(), 2);
+    }
+
+    #[test]
+    fn restored_cursors_resume_interrupted_tree() {
+        // StatefulSequence( S, StatefulSelector(F, R, S), S )
+        fn build() -> (BehaviorTree, Vec<crate::testing::Probe>) {
+            let nodes = [
+                ScriptNode::new(&[Status::Success]),
+                ScriptNode::new(&[Status::Failure]),
+                ScriptNode::new(&[Status::Running, Status::Success]),
+                ScriptNode::new(&[Status::Success]),
+                ScriptNode::new(&[Status::Success]),
+            ];
+            let probes = nodes.iter().map(ScriptNode::probe).collect();
+            let [a, b, c, d, e] = nodes.map(|node| Box::new(node) as Box<dyn Node>);
+            let selector = StatefulSelector::new(vec![b, c, d]);
+            let root = StatefulSequence::new(vec![a, Box::new(selector), e]);
+            (BehaviorTree::new(Box::new(root)), probes)
+        }
+
+        let (mut tree, _) = build();
+        assert_eq!(tree.tick(), Status::Running);
+        let cursors = tree.save_cursors();
+        assert_eq!(cursors, [1, 1]);
+
+        let (mut restored, probes) = build();
+        assert_eq!(
+            restored.restore_cursors(&[1]),
+            Err(TreeError::CursorCount { expected: 2, found: 1 })
+        );
+        assert_eq!(
+            restored.restore_cursors(&[1, 3]),
+            Err(TreeError::CursorOutOfRange { position: 1, index: 3, len: 3 })
+        );
+        assert_eq!(restored.save_cursors(), [0, 0]);
+
+        restored.restore_cursors(&cursors).unwrap();
+        assert_eq!(restored.tick(), Status::Running);
+        // 从中断处继续：已完成的子节点不再执行
+        let updates: Vec<_> = probes.iter().map(|probe| probe.updates()).collect();
+        assert_eq!(updates, [0, 0, 1, 0, 0]);
+    }
+
+    #[test]
+    fn nesting_past_max_depth_is_an_error() {
+        let mut node: Box<dyn Node> = Box::new(ScriptNode::new(&[Status::Success]));
+        for _ in 0..DEFAULT_MAX_DEPTH + 1 {
+            node = Box::new(decorator::Once::new(node));
+        }
+        let mut tree = BehaviorTree::new(node);
+        let exceeded = TreeError::MaxDepthExceeded { limit: DEFAULT_MAX_DEPTH };
+        assert_eq!(tree.check_depth(), Err(exceeded.clone()));
+        assert_eq!(tree.try_tick(), Err(exceeded.clone()));
+        assert_eq!(tree.visit(&mut |_, _| {}), Err(exceeded.clone()));
+        assert_eq!(tree.restore_cursors(&[]), Err(exceeded));
+
+        tree.set_max_depth(DEFAULT_MAX_DEPTH + 1);
+        assert_eq!(tree.check_depth(), Ok(DEFAULT_MAX_DEPTH + 1));
+        assert_eq!(tree.try_tick(), Ok(Status::Success));
+    }
+}
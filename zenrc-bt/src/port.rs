@@ -0,0 +1,168 @@
+//! 端口：节点声明依赖的黑板键及其类型，在首次 tick 前校验接线
+use std::any::{TypeId, type_name};
+
+use thiserror::Error;
+
+use crate::{BlackboardPtr, Node};
+
+/// 端口方向
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PortDirection {
+    /// 节点从黑板读取的键
+    Input,
+    /// 节点向黑板写入的键
+    Output,
+}
+
+/// 节点通过 [`Node::ports`] 声明的一个端口
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PortSpec {
+    /// 黑板键
+    pub name: String,
+    /// 值的类型
+    pub type_id: TypeId,
+    /// 值的类型名，用于错误信息
+    pub type_name: &'static str,
+    pub direction: PortDirection,
+}
+
+impl PortSpec {
+    /// 读取 T 类型值的输入端口
+    pub fn input<T: 'static>(name: &str) -> Self {
+        Self::new::<T>(name, PortDirection::Input)
+    }
+
+    /// 写入 T 类型值的输出端口
+    pub fn output<T: 'static>(name: &str) -> Self {
+        Self::new::<T>(name, PortDirection::Output)
+    }
+
+    fn new<T: 'static>(name: &str, direction: PortDirection) -> Self {
+        Self {
+            name: name.to_string(),
+            type_id: TypeId::of::<T>(),
+            type_name: type_name::<T>(),
+            direction,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+pub enum PortError {
+    #[error("{node}: input port `{port}` has no value on the blackboard and no producer")]
+    Missing { node: String, port: String },
+    #[error("{node}: port `{port}` is bound to a type other than `{expected}`")]
+    TypeMismatch {
+        node: String,
+        port: String,
+        expected: &'static str,
+    },
+}
+
+/// 校验以 root 为根的树中每个输入端口：黑板上已有同类型的值，或有节点声明了同名同类型的输出端口
+///
+/// 黑板上的值或输出端口类型不符时返回 [`PortError::TypeMismatch`]，两者都没有时返回
+/// [`PortError::Missing`]；只返回先序遍历中遇到的第一个错误。
+pub fn validate(root: &dyn Node, blackboard: &BlackboardPtr) -> Result<(), PortError> {
+    let mut outputs = Vec::new();
+    let mut inputs = Vec::new();
+    root.visit(&mut |node, _| {
+        for port in node.ports() {
+            match port.direction {
+                PortDirection::Input => inputs.push((node.name().to_string(), port)),
+                PortDirection::Output => outputs.push(port),
+            }
+        }
+    });
+
+    for (node, port) in inputs {
+        let mismatch = || PortError::TypeMismatch {
+            node: node.clone(),
+            port: port.name.clone(),
+            expected: port.type_name,
+        };
+        if let Some(type_id) = blackboard.type_id_of(&port.name) {
+            if type_id != port.type_id {
+                return Err(mismatch());
+            }
+            continue;
+        }
+        let mut producers = outputs.iter().filter(|output| output.name == port.name).peekable();
+        if producers.peek().is_none() {
+            return Err(PortError::Missing { node, port: port.name });
+        }
+        if producers.any(|output| output.type_id != port.type_id) {
+            return Err(mismatch());
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{BaseNode, BehaviorTree, Sequence, Status};
+
+    // 声明一个端口的叶子节点
+    struct PortNode {
+        base: BaseNode,
+        port: PortSpec,
+    }
+
+    impl Node for PortNode {
+        fn get_blackboard(&self) -> Option<BlackboardPtr> {
+            self.base.get_blackboard()
+        }
+        fn set_blackboard(&mut self, bb: BlackboardPtr) {
+            self.base.set_blackboard(bb);
+        }
+        fn get_status(&self) -> Status {
+            self.base.get_status()
+        }
+        fn set_status(&mut self, s: Status) {
+            self.base.set_status(s);
+        }
+        fn ports(&self) -> Vec<PortSpec> {
+            vec![self.port.clone()]
+        }
+        fn update(&mut self) -> Status {
+            Status::Success
+        }
+    }
+
+    fn port_node(port: PortSpec) -> Box<dyn Node> {
+        Box::new(PortNode { base: BaseNode::new(), port })
+    }
+
+    #[test]
+    fn input_port_needs_value_of_declared_type() {
+        let tree = BehaviorTree::new(port_node(PortSpec::input::<i32>("speed")));
+        let err = tree.validate_ports().unwrap_err();
+        assert_eq!(
+            err,
+            PortError::Missing { node: "PortNode".to_string(), port: "speed".to_string() }
+        );
+
+        tree.blackboard().set("speed", 1.5f64);
+        let err = tree.validate_ports().unwrap_err();
+        assert!(matches!(err, PortError::TypeMismatch { expected: "i32", .. }), "{}", err);
+
+        tree.blackboard().set("speed", 3i32);
+        assert_eq!(tree.validate_ports(), Ok(()));
+    }
+
+    #[test]
+    fn output_port_satisfies_input() {
+        let tree = BehaviorTree::new(Box::new(Sequence::new(vec![
+            port_node(PortSpec::output::<i32>("speed")),
+            port_node(PortSpec::input::<i32>("speed")),
+        ])));
+        assert_eq!(tree.validate_ports(), Ok(()));
+
+        let tree = BehaviorTree::new(Box::new(Sequence::new(vec![
+            port_node(PortSpec::output::<u8>("speed")),
+            port_node(PortSpec::input::<i32>("speed")),
+        ])));
+        assert!(matches!(tree.validate_ports(), Err(PortError::TypeMismatch { .. })));
+    }
+}
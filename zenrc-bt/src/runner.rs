@@ -0,0 +1,380 @@
+//! 定频执行：按固定频率 tick 行为树，替代手写的 sleep 循环
+//!
+//! 另有事件驱动的 [`ReactiveRunner`]，只重新执行监听的黑板键发生变化的子树。
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::{Duration, Instant};
+
+use crate::{BehaviorTree, Node, Status};
+
+// 监听键在节点上次实际 tick 时的版本号，以节点地址区分不同节点
+type Seen = HashMap<(usize, String), u64>;
+
+thread_local! {
+    // ReactiveRunner::tick 期间从执行器借出的版本记录，其余时间为 None
+    static SEEN: RefCell<Option<Seen>> = const { RefCell::new(None) };
+}
+
+/// 一次 [`TreeRunner::run_at_hz`] 的 tick 统计
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct TickStats {
+    /// 执行的 tick 次数
+    pub ticks: u64,
+    /// 单次 tick 的平均耗时
+    pub mean: Duration,
+    /// 单次 tick 的最大耗时
+    pub max: Duration,
+    /// 超时次数：tick 结束时已错过下一周期的起点
+    pub overruns: u64,
+}
+
+/// 持有根节点并按固定频率 tick 的执行器
+pub struct TreeRunner {
+    root: Box<dyn Node>,
+}
+
+impl TreeRunner {
+    pub fn new(root: Box<dyn Node>) -> Self {
+        Self { root }
+    }
+
+    pub fn root(&self) -> &dyn Node {
+        self.root.as_ref()
+    }
+
+    pub fn root_mut(&mut self) -> &mut dyn Node {
+        self.root.as_mut()
+    }
+
+    pub fn into_root(self) -> Box<dyn Node> {
+        self.root
+    }
+
+    /// 以 hz 的频率 tick 根节点，直到 stop 被置为 true，返回统计信息
+    ///
+    /// 每个周期的起点按绝对时间计算（起始时刻 + n 个周期），tick 耗时不会累积成漂移。
+    /// 发生超时时不补偿错过的周期，而是从当前时刻重新排程，避免连续突发 tick。
+    /// stop 在每次 tick 前检查。
+    pub fn run_at_hz(&mut self, hz: f64, stop: Arc<AtomicBool>) -> TickStats {
+        assert!(hz.is_finite() && hz > 0.0, "tick rate must be positive");
+        let period = Duration::from_secs_f64(1.0 / hz);
+        let mut stats = TickStats::default();
+        let mut total = Duration::ZERO;
+        let mut next = Instant::now();
+
+        while !stop.load(Ordering::Acquire) {
+            let start = Instant::now();
+            self.root.tick();
+            let now = Instant::now();
+            let elapsed = now - start;
+
+            stats.ticks += 1;
+            stats.max = stats.max.max(elapsed);
+            total += elapsed;
+
+            next += period;
+            if now > next {
+                stats.overruns += 1;
+                next = now;
+            } else {
+                std::thread::sleep(next - now);
+            }
+        }
+
+        if stats.ticks > 0 {
+            stats.mean = total / stats.ticks as u32;
+        }
+        stats
+    }
+
+    /// 连续 tick 根节点直到结束或达到 max_ticks，见 [`tick_until_terminal`]
+    ///
+    /// restart 为 true 时先中断并重置整棵树，保证从头执行；否则正在运行的树从中断处继续。
+    pub fn tick_until_terminal(&mut self, max_ticks: usize, restart: bool) -> (Status, usize) {
+        if restart {
+            self.root.halt();
+        }
+        tick_until_terminal(self.root.as_mut(), max_ticks)
+    }
+}
+
+/// 事件驱动的执行器：只重新 tick 监听的键（[`Node::watch_keys`]）发生变化的子树
+///
+/// 其余已结束的子树直接返回上次的状态，适合大部分节点只在特定输入变化时才需要重新计算的树。
+/// “变化”指自该节点上一次实际 tick 以来，键通过黑板的类型化接口被写入过，
+/// 见 [`BlackboardPtr::version`]；因此本次 tick 未执行到的节点不会错过期间的变化。
+/// 运行中的节点和没有声明监听键的节点照常执行。
+///
+/// [`BlackboardPtr::version`]: crate::BlackboardPtr::version
+pub struct ReactiveRunner {
+    tree: BehaviorTree,
+    seen: Seen,
+}
+
+impl ReactiveRunner {
+    pub fn new(tree: BehaviorTree) -> Self {
+        Self {
+            tree,
+            seen: HashMap::new(),
+        }
+    }
+
+    pub fn tree(&self) -> &BehaviorTree {
+        &self.tree
+    }
+
+    pub fn tree_mut(&mut self) -> &mut BehaviorTree {
+        &mut self.tree
+    }
+
+    pub fn into_tree(self) -> BehaviorTree {
+        self.tree
+    }
+
+    /// 对整棵树执行一次 tick，跳过监听的键未变化且已结束的子树
+    pub fn tick(&mut self) -> Status {
+        SEEN.with(|s| *s.borrow_mut() = Some(std::mem::take(&mut self.seen)));
+        let status = self.tree.tick();
+        self.seen = SEEN.with(|s| s.borrow_mut().take()).unwrap_or_default();
+        status
+    }
+}
+
+/// 节点进入 tick 时调用，返回 true 表示本次沿用上次的状态而不执行
+///
+/// 只在 [`ReactiveRunner::tick`] 中生效：节点声明了监听键、已结束，且这些键在它上次
+/// 实际 tick 之后都未被写入时返回 true；否则记录监听键的当前版本号并返回 false。
+pub(crate) fn reuse_status(node: &dyn Node) -> bool {
+    let keys = node.watch_keys();
+    if keys.is_empty() {
+        return false;
+    }
+    SEEN.with(|s| {
+        let mut s = s.borrow_mut();
+        let (Some(seen), Some(blackboard)) = (s.as_mut(), node.get_blackboard()) else {
+            return false;
+        };
+        let id = node as *const dyn Node as *const () as usize;
+        let finished = matches!(node.get_status(), Status::Success | Status::Failure);
+        let unchanged = keys.iter().all(|&key| {
+            seen.get(&(id, key.to_string())) == Some(&blackboard.version(key))
+        });
+        if finished && unchanged {
+            return true;
+        }
+        for &key in keys {
+            seen.insert((id, key.to_string()), blackboard.version(key));
+        }
+        false
+    })
+}
+
+/// 连续 tick root 直到返回非 Running 的状态或 tick 次数达到 max_ticks
+///
+/// 返回最后一次 tick 的状态和实际 tick 次数，用于测试和固定步长仿真。
+/// 达到上限时返回 Running，树保持运行状态，可以再次调用继续执行；max_ticks 为 0 时不 tick。
+pub fn tick_until_terminal(root: &mut dyn Node, max_ticks: usize) -> (Status, usize) {
+    let mut status = root.get_status();
+    for ticks in 1..=max_ticks {
+        status = root.tick();
+        if status != Status::Running {
+            return (status, ticks);
+        }
+    }
+    (status, max_ticks)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cell::Cell;
+    use std::rc::Rc;
+
+    use crate::testing::ScriptNode;
+    use crate::{BaseNode, BlackboardPtr, Selector, Sequence};
+
+    // 每次 tick 耗时 work，执行 ticks 次后置位 stop
+    struct StopAfter {
+        base: BaseNode,
+        remaining: usize,
+        work: Duration,
+        stop: Arc<AtomicBool>,
+    }
+
+    impl Node for StopAfter {
+        fn get_blackboard(&self) -> Option<BlackboardPtr> {
+            self.base.get_blackboard()
+        }
+        fn set_blackboard(&mut self, bb: BlackboardPtr) {
+            self.base.set_blackboard(bb);
+        }
+        fn get_status(&self) -> Status {
+            self.base.get_status()
+        }
+        fn set_status(&mut self, s: Status) {
+            self.base.set_status(s);
+        }
+        fn update(&mut self) -> Status {
+            std::thread::sleep(self.work);
+            self.remaining -= 1;
+            if self.remaining == 0 {
+                self.stop.store(true, Ordering::Release);
+            }
+            Status::Success
+        }
+    }
+
+    fn runner(ticks: usize, work: Duration, stop: &Arc<AtomicBool>) -> TreeRunner {
+        TreeRunner::new(Box::new(StopAfter {
+            base: BaseNode::new(),
+            remaining: ticks,
+            work,
+            stop: stop.clone(),
+        }))
+    }
+
+    #[test]
+    fn run_at_hz_collects_statistics() {
+        let stop = Arc::new(AtomicBool::new(false));
+        let started = Instant::now();
+        let stats = runner(5, Duration::from_millis(1), &stop).run_at_hz(200.0, stop);
+
+        assert_eq!(stats.ticks, 5);
+        assert!(stats.mean >= Duration::from_millis(1));
+        assert!(stats.max >= stats.mean);
+        // 5 个周期共 25ms，未超时时 tick 之间按周期等待
+        assert!(stats.overruns > 0 || started.elapsed() >= Duration::from_millis(20));
+    }
+
+    #[test]
+    fn slow_ticks_count_as_overruns() {
+        let stop = Arc::new(AtomicBool::new(false));
+        let stats = runner(3, Duration::from_millis(5), &stop).run_at_hz(1000.0, stop);
+
+        assert_eq!(stats.ticks, 3);
+        assert_eq!(stats.overruns, 3);
+        assert!(stats.max >= Duration::from_millis(5));
+    }
+
+    #[test]
+    fn tick_until_terminal_counts_ticks() {
+        let script = [Status::Running, Status::Running, Status::Success];
+        let mut root = ScriptNode::new(&script);
+        assert_eq!(tick_until_terminal(&mut root, 10), (Status::Success, 3));
+
+        // 达到上限时仍在运行，可以继续执行
+        let mut runner = TreeRunner::new(Box::new(ScriptNode::new(&script)));
+        assert_eq!(runner.tick_until_terminal(2, false), (Status::Running, 2));
+        assert_eq!(runner.tick_until_terminal(2, false), (Status::Success, 1));
+    }
+
+    // 监听一个黑板键的叶子节点，记录 update 次数
+    struct Watcher {
+        base: BaseNode,
+        keys: [&'static str; 1],
+        updates: Rc<Cell<usize>>,
+    }
+
+    impl Node for Watcher {
+        fn get_blackboard(&self) -> Option<BlackboardPtr> {
+            self.base.get_blackboard()
+        }
+        fn set_blackboard(&mut self, bb: BlackboardPtr) {
+            self.base.set_blackboard(bb);
+        }
+        fn get_status(&self) -> Status {
+            self.base.get_status()
+        }
+        fn set_status(&mut self, s: Status) {
+            self.base.set_status(s);
+        }
+        fn watch_keys(&self) -> &[&str] {
+            &self.keys
+        }
+        // 监听的键为 false 时失败，否则成功
+        fn update(&mut self) -> Status {
+            self.updates.set(self.updates.get() + 1);
+            let bb = self.blackboard_or_default();
+            match bb.get_bool(self.keys[0]) {
+                Some(false) => Status::Failure,
+                _ => Status::Success,
+            }
+        }
+    }
+
+    fn watcher(key: &'static str) -> (Box<dyn Node>, Rc<Cell<usize>>) {
+        let updates = Rc::new(Cell::new(0));
+        let node = Watcher {
+            base: BaseNode::new(),
+            keys: [key],
+            updates: updates.clone(),
+        };
+        (Box::new(node), updates)
+    }
+
+    #[test]
+    fn reactive_runner_reticks_only_changed_watchers() {
+        let (goal, goal_updates) = watcher("goal");
+        let (battery, battery_updates) = watcher("battery");
+        let tree = BehaviorTree::new(Box::new(Sequence::new(vec![goal, battery])));
+        let mut runner = ReactiveRunner::new(tree);
+        let updates = || (goal_updates.get(), battery_updates.get());
+
+        assert_eq!(runner.tick(), Status::Success);
+        assert_eq!(updates(), (1, 1));
+
+        runner.tree().blackboard().set("goal", 3_i32);
+        assert_eq!(runner.tick(), Status::Success);
+        assert_eq!(updates(), (2, 1));
+
+        assert_eq!(runner.tick(), Status::Success);
+        assert_eq!(updates(), (2, 1));
+
+        // 普通 tick 不跳过任何节点
+        runner.tree_mut().tick();
+        assert_eq!(updates(), (3, 2));
+    }
+
+    #[test]
+    fn reactive_runner_sees_changes_made_while_node_was_skipped() {
+        let (first, first_updates) = watcher("x");
+        let (second, second_updates) = watcher("y");
+        let tree = BehaviorTree::new(Box::new(Selector::new(vec![first, second])));
+        tree.blackboard().set("x", false);
+        let mut runner = ReactiveRunner::new(tree);
+        let updates = || (first_updates.get(), second_updates.get());
+
+        assert_eq!(runner.tick(), Status::Success);
+        assert_eq!(updates(), (1, 1));
+
+        // 第一个子节点成功，选择器短路，第二个子节点未执行，期间 y 发生变化
+        runner.tree().blackboard().set("x", true);
+        runner.tree().blackboard().set("y", false);
+        assert_eq!(runner.tick(), Status::Success);
+        assert_eq!(updates(), (2, 1));
+
+        // 第二个子节点再次被执行到时必须感知到 y 的变化
+        runner.tree().blackboard().set("x", false);
+        assert_eq!(runner.tick(), Status::Failure);
+        assert_eq!(updates(), (3, 2));
+    }
+
+    #[test]
+    fn reactive_runner_sees_writes_through_deref() {
+        let (enabled, updates) = watcher("enabled");
+        let tree = BehaviorTree::new(enabled);
+        let mut runner = ReactiveRunner::new(tree);
+
+        assert_eq!(runner.tick(), Status::Success);
+        assert_eq!(runner.tick(), Status::Success);
+        assert_eq!(updates.get(), 1);
+
+        // 绕过 set 直接写 map 同样会让监听的节点重新执行
+        let bb = runner.tree().blackboard();
+        bb.borrow_mut().insert("enabled".to_string(), Box::new(false));
+        assert_eq!(runner.tick(), Status::Failure);
+        assert_eq!(updates.get(), 2);
+    }
+}
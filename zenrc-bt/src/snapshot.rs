@@ -0,0 +1,122 @@
+//! 黑板快照：深拷贝黑板内容，之后可整体恢复，用于保存/加载和回滚试探性行为
+//!
+//! 黑板中的值是 `Box<dyn Any>`，拷贝需要知道具体类型，因此参与快照的类型要先通过
+//! [`register`] 注册。注册表是进程级的，通常在程序启动时注册一次即可。
+use std::any::{Any, TypeId};
+use std::collections::HashMap;
+use std::sync::{OnceLock, RwLock};
+
+use crate::BlackboardPtr;
+
+type CloneFn = fn(&dyn Any) -> Box<dyn Any>;
+
+fn registry() -> &'static RwLock<HashMap<TypeId, CloneFn>> {
+    static REGISTRY: OnceLock<RwLock<HashMap<TypeId, CloneFn>>> = OnceLock::new();
+    REGISTRY.get_or_init(Default::default)
+}
+
+fn clone_value<T: Clone + 'static>(value: &dyn Any) -> Box<dyn Any> {
+    let value = value
+        .downcast_ref::<T>()
+        .expect("clone fn registered for another type");
+    Box::new(value.clone())
+}
+
+/// 注册可参与快照的类型，重复注册无副作用
+pub fn register<T: Clone + 'static>() {
+    registry()
+        .write()
+        .unwrap()
+        .insert(TypeId::of::<T>(), clone_value::<T>);
+}
+
+/// 黑板快照，由 [`BlackboardPtr::snapshot`] 创建，可多次用于 [`BlackboardPtr::restore`]
+pub struct BlackboardSnapshot {
+    entries: HashMap<String, (Box<dyn Any>, CloneFn)>,
+}
+
+impl BlackboardSnapshot {
+    /// 快照中的条目数
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// 快照中是否包含 key
+    pub fn contains_key(&self, key: &str) -> bool {
+        self.entries.contains_key(key)
+    }
+}
+
+impl BlackboardPtr {
+    /// 深拷贝所有已注册类型的条目；未注册类型的条目被跳过并输出警告
+    pub fn snapshot(&self) -> BlackboardSnapshot {
+        let registry = registry().read().unwrap();
+        let mut entries = HashMap::new();
+        for (key, value) in self.borrow().iter() {
+            // 注意取的是值的 TypeId，而不是 Box 本身的
+            match registry.get(&value.as_ref().type_id()) {
+                Some(&clone) => {
+                    entries.insert(key.clone(), (clone(value.as_ref()), clone));
+                }
+                None => eprintln!(
+                    "blackboard snapshot: skip key `{}` of unregistered type",
+                    key
+                ),
+            }
+        }
+        BlackboardSnapshot { entries }
+    }
+
+    /// 用快照替换黑板的全部内容，快照之后新增的条目会被移除
+    pub fn restore(&self, snapshot: &BlackboardSnapshot) {
+        let map = snapshot
+            .entries
+            .iter()
+            .map(|(key, (value, clone))| (key.clone(), clone(value.as_ref())))
+            .collect();
+        *self.borrow_mut() = map;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct Unregistered;
+
+    #[test]
+    fn restore_rolls_back_registered_entries() {
+        register::<i32>();
+        register::<String>();
+        let bb = BlackboardPtr::new();
+        bb.borrow_mut().insert("count".to_string(), Box::new(1));
+        bb.borrow_mut()
+            .insert("name".to_string(), Box::new("patrol".to_string()));
+        bb.borrow_mut()
+            .insert("opaque".to_string(), Box::new(Unregistered));
+
+        let snapshot = bb.snapshot();
+        assert_eq!(snapshot.len(), 2);
+        assert!(!snapshot.contains_key("opaque"));
+
+        *bb.entry_or_insert_with("count", || 0) += 10;
+        bb.entry_or_insert_with("name", String::new)
+            .push_str("-changed");
+        bb.borrow_mut().insert("extra".to_string(), Box::new(3));
+
+        bb.restore(&snapshot);
+        assert_eq!(*bb.get::<i32>("count").unwrap(), 1);
+        assert_eq!(*bb.get::<String>("name").unwrap(), "patrol");
+        assert!(bb.get::<i32>("extra").is_none());
+        assert!(bb.get::<Unregistered>("opaque").is_none());
+
+        // 快照可重复使用
+        *bb.entry_or_insert_with("count", || 0) += 1;
+        bb.restore(&snapshot);
+        assert_eq!(*bb.get::<i32>("count").unwrap(), 1);
+    }
+}
@@ -0,0 +1,68 @@
+//! 单元测试共用的辅助节点
+use std::cell::Cell;
+use std::rc::Rc;
+
+use crate::{BaseNode, BlackboardPtr, Node, Status};
+
+/// 记录节点回调次数的探针，可在节点被装箱后继续观察
+#[derive(Clone, Default)]
+pub(crate) struct Probe {
+    updates: Rc<Cell<usize>>,
+    terminates: Rc<Cell<usize>>,
+}
+
+impl Probe {
+    pub(crate) fn updates(&self) -> usize {
+        self.updates.get()
+    }
+    pub(crate) fn terminates(&self) -> usize {
+        self.terminates.get()
+    }
+}
+
+/// 按脚本依次返回状态的叶子节点，脚本用完后重复最后一个状态
+pub(crate) struct ScriptNode {
+    base: BaseNode,
+    script: Vec<Status>,
+    probe: Probe,
+}
+
+impl ScriptNode {
+    pub(crate) fn new(script: &[Status]) -> Self {
+        assert!(!script.is_empty(), "script must not be empty");
+        Self {
+            base: BaseNode::new(),
+            script: script.to_vec(),
+            probe: Probe::default(),
+        }
+    }
+    pub(crate) fn probe(&self) -> Probe {
+        self.probe.clone()
+    }
+}
+
+impl Node for ScriptNode {
+    fn get_blackboard(&self) -> Option<BlackboardPtr> {
+        self.base.get_blackboard()
+    }
+    fn set_blackboard(&mut self, bb: BlackboardPtr) {
+        self.base.set_blackboard(bb);
+    }
+    fn get_status(&self) -> Status {
+        self.base.get_status()
+    }
+    fn set_status(&mut self, s: Status) {
+        self.base.set_status(s);
+    }
+    fn clone_node(&self) -> Box<dyn Node> {
+        Box::new(ScriptNode::new(&self.script))
+    }
+    fn terminate(&mut self) {
+        self.probe.terminates.set(self.probe.terminates.get() + 1);
+    }
+    fn update(&mut self) -> Status {
+        let n = self.probe.updates.get();
+        self.probe.updates.set(n + 1);
+        self.script[n.min(self.script.len() - 1)]
+    }
+}
@@ -0,0 +1,174 @@
+//! tick 追踪：按执行顺序记录一次 tick 中被执行的节点及其结果
+//!
+//! 追踪器通过线程局部变量挂载，默认 `tick()` 在进入/退出时检查是否挂载，
+//! 未挂载时只有一次线程局部读取的开销。
+use std::cell::RefCell;
+
+use crate::{Node, Status};
+
+/// 一条追踪记录
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TraceEntry {
+    /// 节点名称（`Node::name()`）
+    pub name: String,
+    /// 节点在本次 tick 调用栈中的深度，根节点为 0
+    pub depth: usize,
+    /// 节点本次 tick 的返回状态
+    pub status: Status,
+}
+
+#[derive(Default)]
+struct Recorder {
+    entries: Vec<TraceEntry>,
+    // 尚未返回的节点在 entries 中的下标
+    stack: Vec<usize>,
+}
+
+thread_local! {
+    static RECORDER: RefCell<Option<Recorder>> = const { RefCell::new(None) };
+}
+
+/// 记录一次 tick 中节点执行顺序的追踪器
+///
+/// 记录按先序排列：父节点在其子节点之前，同层节点按执行先后排列。
+#[derive(Debug, Default)]
+pub struct TickTracer {
+    entries: Vec<TraceEntry>,
+}
+
+impl TickTracer {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 挂载追踪器对 root 执行一次 tick，覆盖上一次的记录
+    pub fn tick(&mut self, root: &mut dyn Node) -> Status {
+        RECORDER.with(|r| *r.borrow_mut() = Some(Recorder::default()));
+        let status = root.tick();
+        let recorder = RECORDER.with(|r| r.borrow_mut().take());
+        self.entries = recorder.map(|r| r.entries).unwrap_or_default();
+        status
+    }
+
+    /// 最近一次 tick 的追踪记录
+    pub fn entries(&self) -> &[TraceEntry] {
+        &self.entries
+    }
+}
+
+/// 节点进入 tick 时调用，返回记录下标；未挂载追踪器时返回 None
+pub(crate) fn enter(name: &str) -> Option<usize> {
+    RECORDER.with(|r| {
+        let mut r = r.borrow_mut();
+        let recorder = r.as_mut()?;
+        let index = recorder.entries.len();
+        recorder.entries.push(TraceEntry {
+            name: name.to_string(),
+            depth: recorder.stack.len(),
+            status: Status::Invalid,
+        });
+        recorder.stack.push(index);
+        Some(index)
+    })
+}
+
+/// 节点退出 tick 时调用，回填返回状态
+pub(crate) fn exit(index: Option<usize>, status: Status) {
+    let Some(index) = index else {
+        return;
+    };
+    RECORDER.with(|r| {
+        if let Some(recorder) = r.borrow_mut().as_mut() {
+            recorder.stack.pop();
+            if let Some(entry) = recorder.entries.get_mut(index) {
+                entry.status = status;
+            }
+        }
+    });
+}
+
+/// 整棵树当前状态的快照，可序列化后发送给监控面板
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct StatusSnapshot {
+    /// 节点名称（`Node::name()`）
+    pub name: String,
+    /// 节点当前状态
+    pub status: Status,
+    /// 子节点快照，顺序与 `Node::children()` 一致
+    pub children: Vec<StatusSnapshot>,
+}
+
+/// 遍历以 node 为根的子树，记录每个节点的名称和当前状态
+pub fn status_snapshot(node: &dyn Node) -> StatusSnapshot {
+    StatusSnapshot {
+        name: node.name().to_string(),
+        status: node.get_status(),
+        children: node.children().iter().map(|child| status_snapshot(child.as_ref())).collect(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Sequence;
+    use crate::testing::ScriptNode;
+
+    #[test]
+    fn traces_sequence_children_in_order() {
+        let mut root = Sequence::new(vec![
+            Box::new(ScriptNode::new(&[Status::Success])),
+            Box::new(ScriptNode::new(&[Status::Success])),
+            Box::new(ScriptNode::new(&[Status::Failure])),
+        ]);
+        let mut tracer = TickTracer::new();
+        assert_eq!(tracer.tick(&mut root), Status::Failure);
+
+        let trace: Vec<_> = tracer
+            .entries()
+            .iter()
+            .map(|e| (e.name.as_str(), e.depth, e.status))
+            .collect();
+        assert_eq!(
+            trace,
+            vec![
+                ("Sequence", 0, Status::Failure),
+                ("ScriptNode", 1, Status::Success),
+                ("ScriptNode", 1, Status::Success),
+                ("ScriptNode", 1, Status::Failure),
+            ]
+        );
+    }
+
+    #[test]
+    fn status_snapshot_mirrors_tree() {
+        let mut root = Sequence::new(vec![
+            Box::new(ScriptNode::new(&[Status::Success])),
+            Box::new(ScriptNode::new(&[Status::Running])),
+            Box::new(ScriptNode::new(&[Status::Success])),
+        ]);
+        root.tick();
+
+        let snapshot = status_snapshot(&root);
+        assert_eq!(snapshot.status, Status::Running);
+        let children: Vec<_> = snapshot.children.iter().map(|c| c.status).collect();
+        assert_eq!(children, [Status::Success, Status::Running, Status::Invalid]);
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn status_snapshot_serializes_to_json() {
+        let mut root = Sequence::new(vec![Box::new(ScriptNode::new(&[Status::Failure]))]);
+        root.tick();
+
+        let json = serde_json::to_value(status_snapshot(&root)).unwrap();
+        assert_eq!(
+            json,
+            serde_json::json!({
+                "name": "Sequence",
+                "status": "Failure",
+                "children": [{ "name": "ScriptNode", "status": "Failure", "children": [] }],
+            })
+        );
+    }
+}
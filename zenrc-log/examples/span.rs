@@ -4,7 +4,7 @@ use zenrc_log::*;
 fn main() {
     let builder = SubscriberBuilder::new();
     builder
-        .with_event_format(LogFormatter)
+        .with_event_format(LogFormatter::new().with_pid(true))
         .with_level(Level::INFO)
         .with_rotation(Period::Minute)
         .with_max_log_files(2)
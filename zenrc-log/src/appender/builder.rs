@@ -3,20 +3,24 @@ use std::fmt::{self, Debug};
 use std::fs::{self, File, OpenOptions};
 use std::io::{self, Write};
 use std::path::{Path, PathBuf};
+use std::sync::Arc;
 use std::time::SystemTime;
 
 use thiserror::Error;
-use time::{Date, Duration, OffsetDateTime, Time, UtcOffset, format_description};
+use time::{Date, Duration, OffsetDateTime, Time, format_description};
 use tracing::Metadata;
 
 use super::sync::{RwLock, RwLockReadGuard};
+use crate::local_time;
 
 #[derive(Debug)]
 pub struct Builder {
     pub(super) rotation: Rotation,
     pub(super) prefix: String,
+    pub(super) suffix: Option<String>,
     pub(super) max_files: Option<usize>,
     pub(super) filters: Option<HashMap<String, String>>,
+    pub(super) align_to_boundary: bool,
 }
 
 /// Errors returned by [`Builder::build`].
@@ -46,15 +50,25 @@ impl From<time::error::IndeterminateOffset> for InitError {
     }
 }
 
+impl From<tracing_subscriber::util::TryInitError> for InitError {
+    fn from(error: tracing_subscriber::util::TryInitError) -> Self {
+        Self {
+            context: "failed to set global default subscriber",
+            source: io::Error::other(error),
+        }
+    }
+}
+
 impl Builder {
     #[must_use]
     pub const fn new() -> Self {
         Self {
             rotation: Rotation::NEVER,
             prefix: String::new(),
-            // suffix: None,
+            suffix: None,
             max_files: None,
             filters: None,
+            align_to_boundary: true,
         }
     }
 
@@ -75,6 +89,18 @@ impl Builder {
         }
     }
 
+    /// 设置日志文件扩展名
+    ///
+    /// 设置后当前文件名为 "{prefix}.{suffix}"，滚动后的文件名为 "{prefix}-{date}.{suffix}"，
+    /// 如 "app.log" 滚动为 "app-2024-01-02.log"；未设置时分别为 "{prefix}" 和 "{prefix}.{date}"
+    #[must_use]
+    pub fn suffix(self, suffix: impl Into<String>) -> Self {
+        Self {
+            suffix: Some(suffix.into()),
+            ..self
+        }
+    }
+
     #[must_use]
     pub fn filter(self, target: impl Into<String>, filename: impl Into<String>) -> Self {
         let target = target.into();
@@ -87,6 +113,18 @@ impl Builder {
         }
     }
 
+    /// 滚动时间是否对齐到周期边界，默认开启
+    ///
+    /// 开启时按整点滚动，如按天滚动的文件在下一个午夜滚动；关闭时从文件创建时间起
+    /// 经过一个完整周期才滚动，如 14:37 创建的文件在次日 14:37 滚动
+    #[must_use]
+    pub fn align_to_boundary(self, align_to_boundary: bool) -> Self {
+        Self {
+            align_to_boundary,
+            ..self
+        }
+    }
+
     #[must_use]
     pub fn max_log_files(self, n: usize) -> Self {
         Self {
@@ -112,6 +150,7 @@ impl Default for Builder {
 pub struct WriterMeta {
     log_directory: PathBuf,
     log_filename: String,
+    suffix: Option<String>,
     // date_format: Vec<format_description::FormatItem<'static>>,
     crate_time: RwLock<OffsetDateTime>,
     max_files: Option<usize>,
@@ -122,19 +161,24 @@ impl WriterMeta {
     fn new(
         directory: impl AsRef<Path>,
         log_filename: String,
+        suffix: Option<String>,
         // rotation: Rotation,
         max_files: Option<usize>,
     ) -> Result<Self, InitError> {
         let log_directory = directory.as_ref().to_path_buf();
         // let date_format = rotation.date_format();
 
+        let active_filename = match &suffix {
+            Some(suffix) => format!("{}.{}", log_filename, suffix),
+            None => log_filename.clone(),
+        };
         let writer: RwLock<File> =
-            RwLock::new(create_writer(log_directory.as_ref(), &log_filename)?);
-        let crate_time = OffsetDateTime::from(writer.read().metadata().unwrap().created().unwrap())
-            .to_offset(UtcOffset::local_offset_at(OffsetDateTime::now_utc()).unwrap());
+            RwLock::new(create_writer(log_directory.as_ref(), &active_filename)?);
+        let crate_time = local_time::to_local(file_time(&writer.read())?);
         Ok(Self {
             log_directory,
             log_filename,
+            suffix,
             // date_format,
             crate_time: RwLock::new(crate_time),
             max_files,
@@ -151,11 +195,55 @@ impl WriterMeta {
             .format(date_format)
             .expect("Unable to format OffsetDateTime; this is a bug in tracing-appender");
 
-        format!("{}.{}", self.log_filename, date)
+        match &self.suffix {
+            Some(suffix) => format!("{}-{}.{}", self.log_filename, date, suffix),
+            None => format!("{}.{}", self.log_filename, date),
+        }
+    }
+
+    // 当前正在写入的文件名
+    fn active_filename(&self) -> String {
+        match &self.suffix {
+            Some(suffix) => format!("{}.{}", self.log_filename, suffix),
+            None => self.log_filename.clone(),
+        }
+    }
+
+    // 判断文件名是否为本 writer 滚动出的日志文件，即 join_date() 生成的文件名
+    // 仅前缀匹配会误删前缀相同的其他 writer 的文件（如 "app" 与 "app.debug"）
+    fn is_rotated_file(
+        &self,
+        filename: &str,
+        date_format: &[format_description::FormatItem<'static>],
+    ) -> bool {
+        let Some(rest) = filename.strip_prefix(self.log_filename.as_str()) else {
+            return false;
+        };
+        let date = match &self.suffix {
+            Some(suffix) => rest
+                .strip_prefix('-')
+                .and_then(|rest| rest.strip_suffix(suffix.as_str()))
+                .and_then(|rest| rest.strip_suffix('.')),
+            None => rest.strip_prefix('.'),
+        };
+        let Some(date) = date else {
+            return false;
+        };
+        matches!(
+            time::parsing::Parsed::new().parse_items(date.as_bytes(), date_format),
+            Ok(rest) if rest.is_empty()
+        )
     }
 
-    //清理旧日志文件
-    fn prune_old_logs(&self, max_files: usize) {
+    // 清理旧日志文件，在当前文件滚动后调用
+    //
+    // max_files 包含正在写入的当前文件，因此只保留最新的 max_files - 1 个滚动文件；
+    // 其他 writer 的文件和目录中的无关文件不计入
+    fn prune_old_logs(
+        &self,
+        max_files: usize,
+        date_format: &Vec<format_description::FormatItem<'static>>,
+    ) {
         let files = fs::read_dir(&self.log_directory).map(|dir| {
             dir.filter_map(|entry| {
                 let entry = entry.ok()?;
@@ -170,11 +258,11 @@ impl WriterMeta {
                 let filename = entry.file_name();
                 // if the filename is not a UTF-8 string, skip it.
                 let filename = filename.to_str()?;
-                if !filename.starts_with(&self.log_filename) {
+                if !self.is_rotated_file(filename, date_format) {
                     return None;
                 }
 
-                let created = metadata.created().ok()?;
+                let created = created_or_modified(metadata.created(), || metadata.modified()).ok()?;
                 Some((entry, created))
             })
             .collect::<Vec<_>>()
@@ -187,15 +275,15 @@ impl WriterMeta {
                 return;
             }
         };
-        if files.len() < max_files {
+        let keep = max_files.saturating_sub(1);
+        if files.len() <= keep {
             return;
         }
 
         // sort the files by their creation timestamps.
         files.sort_by_key(|(_, created_at)| *created_at);
 
-        // delete files, so that (n-1) files remain, because we will create another log file
-        for (file, _) in files.iter().take(files.len() - (max_files - 1)) {
+        for (file, _) in files.iter().take(files.len() - keep) {
             if let Err(error) = fs::remove_file(file.path()) {
                 eprintln!(
                     "Failed to remove old log file {}: {}",
@@ -206,50 +294,75 @@ impl WriterMeta {
         }
     }
 
+    // 滚动当前文件，之后 crate_time 推进到 period_start（新周期的起点）
     fn refresh_writer(
         &self,
         file: &mut File,
         date_format: &Vec<format_description::FormatItem<'static>>,
+        period_start: OffsetDateTime,
     ) {
         let filename = self.join_date(&self.crate_time.read(), date_format);
-
+        // 无论滚动是否成功都推进到新周期：失败时继续写入当前文件，
+        // 推迟到下一个滚动周期再重试，避免之后每条日志都触发滚动
+        *self.crate_time.write() = period_start;
+
+        if let Err(err) = fs::rename(
+            self.log_directory.join(self.active_filename()),
+            self.log_directory.join(&filename),
+        ) {
+            eprintln!("Couldn't rotate log file to {}: {}", filename, err);
+            return;
+        }
         if let Some(max_files) = self.max_files {
-            self.prune_old_logs(max_files);
+            self.prune_old_logs(max_files, date_format);
         }
-        fs::rename(
-            self.log_directory.join(&self.log_filename),
-            self.log_directory.join(filename),
-        )
-        .unwrap();
-        match create_writer(&self.log_directory, &self.log_filename) {
+        match create_writer(&self.log_directory, &self.active_filename()) {
             Ok(new_file) => {
                 if let Err(err) = file.flush() {
                     eprintln!("Couldn't flush previous writer: {}", err);
                 }
-                *self.crate_time.write() =
-                    get_current_time(new_file.metadata().unwrap().created().unwrap());
                 *file = new_file;
             }
             Err(err) => eprintln!("Couldn't create writer for logs: {}", err),
         }
     }
 
-    // 检查是否需要滚动日志文件
-    fn should_rollover(&self, rotation: &Rotation) -> bool {
-        let now = OffsetDateTime::now_local().expect("Failed to get local time");
-        // Should we try to roll over the log file?
-        if let Some(time) = rotation.next_date(&self.crate_time.read()) {
-            if now >= time {
-                return true;
-            }
+    // 检查是否需要滚动日志文件，需要时返回 now 所在周期的起点
+    //
+    // 起点由 crate_time 按周期逐次推进得到，不读取文件元数据，保证每个周期恰好滚动一次；
+    // 跨过多个周期时只滚动一次
+    fn next_period(
+        &self,
+        rotation: &Rotation,
+        align: bool,
+        now: OffsetDateTime,
+    ) -> Option<OffsetDateTime> {
+        let mut start = rotation.next_date(&self.crate_time.read(), align)?;
+        if now < start {
+            return None;
+        }
+        while let Some(next) = rotation.next_date(&start, align)
+            && next <= now
+        {
+            start = next;
         }
-        false
+        Some(start)
     }
 }
 pub struct RollingFileAppender {
     rotation: Rotation,
+    align_to_boundary: bool,
     date_format: Vec<format_description::FormatItem<'static>>,
-    writers: HashMap<String, WriterMeta>,
+    writers: Arc<HashMap<String, WriterMeta>>,
+}
+
+/// 析构时刷新所有日志文件的守卫，见 [`RollingFileAppender::flush_guard`]
+///
+/// 应绑定到非 `_` 的变量并在 `main` 中持有，否则会被立即析构。
+#[derive(Debug, Default)]
+#[must_use]
+pub struct FlushGuard {
+    writers: Arc<HashMap<String, WriterMeta>>,
 }
 
 #[derive(Debug)]
@@ -283,9 +396,10 @@ impl RollingFileAppender {
         let Builder {
             rotation,
             prefix,
-            // suffix,
+            suffix,
             max_files,
             filters,
+            align_to_boundary,
         } = builder;
 
         let directory = directory.as_ref().to_path_buf();
@@ -295,6 +409,7 @@ impl RollingFileAppender {
         let writer_meta = WriterMeta::new(
             directory.clone(),
             prefix.clone(),
+            suffix.clone(),
             // rotation.clone(),
             *max_files,
         )?;
@@ -306,6 +421,7 @@ impl RollingFileAppender {
                 let writer = WriterMeta::new(
                     directory.clone(),
                     filename.clone(),
+                    suffix.clone(),
                     // rotation.clone(),
                     *max_files,
                 )?;
@@ -315,25 +431,57 @@ impl RollingFileAppender {
 
         //删除旧日志
         if max_files.is_some() {
+            let now = local_time::now_local();
             for writer in writers.values() {
-                if *writer.crate_time.read()
-                    > rotation
-                        .next_date(&get_current_time(
-                            writer.writer.read().metadata().unwrap().created().unwrap(),
-                        ))
-                        .unwrap()
-                {
-                    writer.refresh_writer(&mut writer.writer.write(), &rotation.date_format());
+                if let Some(start) = writer.next_period(rotation, *align_to_boundary, now) {
+                    let date_format = rotation.date_format();
+                    writer.refresh_writer(&mut writer.writer.write(), &date_format, start);
                 }
             }
         }
 
         Ok(Self {
             rotation: rotation.clone(),
+            align_to_boundary: *align_to_boundary,
             date_format: rotation.date_format(),
-            writers,
+            writers: Arc::new(writers),
         })
     }
+
+    /// 刷新所有 writer 的文件并同步到磁盘，返回遇到的第一个错误
+    pub fn flush(&self) -> io::Result<()> {
+        flush_writers(&self.writers)
+    }
+
+    /// 返回一个在析构时调用 [`flush`](Self::flush) 的守卫，
+    /// appender 移交给订阅器后仍可通过它在退出前落盘
+    pub fn flush_guard(&self) -> FlushGuard {
+        FlushGuard {
+            writers: Arc::clone(&self.writers),
+        }
+    }
+
+    // 取出 target 对应的 writer，没有匹配的过滤规则时使用默认 writer；需要滚动时先滚动
+    fn writer_for(&self, target: &str) -> RollingWriter<'_> {
+        let now = local_time::now_local();
+        self.writer_at(target, now)
+    }
+
+    fn writer_at(&self, target: &str, now: OffsetDateTime) -> RollingWriter<'_> {
+        let meta = self
+            .writers
+            .get(target)
+            .unwrap_or_else(|| &self.writers["default"]);
+        let align = self.align_to_boundary;
+        if meta.next_period(&self.rotation, align, now).is_some() {
+            let mut file = meta.writer.write();
+            // 持有写锁后重新检查，其他线程可能已完成本周期的滚动
+            if let Some(start) = meta.next_period(&self.rotation, align, now) {
+                meta.refresh_writer(&mut file, &self.date_format, start);
+            }
+        }
+        RollingWriter(meta.writer.read())
+    }
 }
 
 // 手动写入
@@ -361,28 +509,12 @@ impl RollingFileAppender {
 impl<'a> tracing_subscriber::fmt::writer::MakeWriter<'a> for RollingFileAppender {
     type Writer = RollingWriter<'a>;
 
-    //? 未调用的函数
     fn make_writer(&'a self) -> Self::Writer {
-        RollingWriter(self.writers.get("default").unwrap().writer.read())
+        self.writer_for("default")
     }
 
     fn make_writer_for(&'a self, meta: &Metadata<'_>) -> Self::Writer {
-        if let Some(target) = self.writers.get(meta.target()) {
-            let writer = &target.writer;
-            if target.should_rollover(&self.rotation) {
-                target.refresh_writer(&mut writer.write(), &self.date_format);
-            }
-            return RollingWriter(writer.read());
-        }
-        let writer = &self.writers.get("default").unwrap().writer;
-        if self.writers.get("default").unwrap().should_rollover(&self.rotation)
-        {
-            self.writers
-                .get("default")
-                .unwrap()
-                .refresh_writer(&mut writer.write(), &self.date_format);
-        }
-        RollingWriter(self.writers.get("default").unwrap().writer.read())
+        self.writer_for(meta.target())
     }
 }
 
@@ -392,6 +524,7 @@ impl fmt::Debug for RollingFileAppender {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         f.debug_struct("RollingFileAppender")
             .field("rotation", &self.rotation)
+            .field("align_to_boundary", &self.align_to_boundary)
             .field("writers", &self.writers)
             .finish()
     }
@@ -441,23 +574,16 @@ impl Rotation {
     /// Provides a rotation that never rotates.
     pub const NEVER: Self = Self(RotationKind::Never);
 
-    pub(crate) fn next_date(&self, current_date: &OffsetDateTime) -> Option<OffsetDateTime> {
+    // align 为 true 时对齐到周期边界，否则为 current_date 之后一个完整周期
+    pub(crate) fn next_date(
+        &self,
+        current_date: &OffsetDateTime,
+        align: bool,
+    ) -> Option<OffsetDateTime> {
         let unrounded_next_date = match *self {
-            Rotation::MINUTELY => {
-                let time = Time::from_hms(current_date.hour(), current_date.minute(), 0)
-                    .expect("Invalid time; this is a bug in tracing-appender");
-                current_date.replace_time(time) + Duration::minutes(1)
-            }
-            Rotation::HOURLY => {
-                let time = Time::from_hms(current_date.hour(), 0, 0)
-                    .expect("Invalid time; this is a bug in tracing-appender");
-                current_date.replace_time(time) + Duration::hours(1)
-            }
-            Rotation::DAILY => {
-                let time = Time::from_hms(0, 0, 0)
-                    .expect("Invalid time; this is a bug in tracing-appender");
-                current_date.replace_time(time) + Duration::days(1)
-            }
+            Rotation::MINUTELY => *current_date + Duration::minutes(1),
+            Rotation::HOURLY => *current_date + Duration::hours(1),
+            Rotation::DAILY => *current_date + Duration::days(1),
             Rotation::MONTHLY => {
                 // 当前年月
                 let year = current_date.year();
@@ -469,41 +595,48 @@ impl Rotation {
                 } else {
                     (year, month.next())
                 };
-                Date::from_calendar_date(next_year, next_month, 1)
+                // 下个月没有同一天时（如 1 月 31 日）取该月最后一天
+                let day = current_date
+                    .day()
+                    .min(next_month.length(next_year));
+                Date::from_calendar_date(next_year, next_month, day)
                     .expect("Invalid date; this is a bug in tracing-appender")
-                    .with_time(Time::MIDNIGHT)
+                    .with_time(current_date.time())
                     .assume_offset(current_date.offset()) // 保持当前时区偏移
             }
             Rotation::NEVER => return None,
         };
-        Some(unrounded_next_date)
-        // Some(self.round_date(&unrounded_next_date))
-    }
-
-    // // note that this method will panic if passed a `Rotation::NEVER`.
-    // pub(crate) fn round_date(&self, date: &OffsetDateTime) -> OffsetDateTime {
-    //     match *self {
-    //         Rotation::MINUTELY => {
-    //             let time = Time::from_hms(date.hour(), date.minute(), 0)
-    //                 .expect("Invalid time; this is a bug in tracing-appender");
-    //             date.replace_time(time)
-    //         }
-    //         Rotation::HOURLY => {
-    //             let time = Time::from_hms(date.hour(), 0, 0)
-    //                 .expect("Invalid time; this is a bug in tracing-appender");
-    //             date.replace_time(time)
-    //         }
-    //         Rotation::DAILY => {
-    //             let time = Time::from_hms(0, 0, 0)
-    //                 .expect("Invalid time; this is a bug in tracing-appender");
-    //             date.replace_time(time)
-    //         }
-    //         // Rotation::NEVER is impossible to round.
-    //         Rotation::NEVER => {
-    //             unreachable!("Rotation::NEVER is impossible to round.")
-    //         }
-    //     }
-    // }
+        if align {
+            Some(self.round_date(&unrounded_next_date))
+        } else {
+            Some(unrounded_next_date)
+        }
+    }
+
+    // note that this method will panic if passed a `Rotation::NEVER`.
+    pub(crate) fn round_date(&self, date: &OffsetDateTime) -> OffsetDateTime {
+        match *self {
+            Rotation::MINUTELY => {
+                let time = Time::from_hms(date.hour(), date.minute(), 0)
+                    .expect("Invalid time; this is a bug in tracing-appender");
+                date.replace_time(time)
+            }
+            Rotation::HOURLY => {
+                let time = Time::from_hms(date.hour(), 0, 0)
+                    .expect("Invalid time; this is a bug in tracing-appender");
+                date.replace_time(time)
+            }
+            Rotation::DAILY => date.replace_time(Time::MIDNIGHT),
+            Rotation::MONTHLY => date
+                .replace_day(1)
+                .expect("Invalid date; this is a bug in tracing-appender")
+                .replace_time(Time::MIDNIGHT),
+            // Rotation::NEVER is impossible to round.
+            Rotation::NEVER => {
+                unreachable!("Rotation::NEVER is impossible to round.")
+            }
+        }
+    }
 
     fn date_format(&self) -> Vec<format_description::FormatItem<'static>> {
         match *self {
@@ -517,6 +650,26 @@ impl Rotation {
     }
 }
 
+impl Drop for FlushGuard {
+    fn drop(&mut self) {
+        if let Err(err) = flush_writers(&self.writers) {
+            eprintln!("Failed to flush log files: {}", err);
+        }
+    }
+}
+
+fn flush_writers(writers: &HashMap<String, WriterMeta>) -> io::Result<()> {
+    let mut result = Ok(());
+    for meta in writers.values() {
+        let file = meta.writer.read();
+        let flushed = (&*file).flush().and_then(|()| file.sync_data());
+        if result.is_ok() {
+            result = flushed;
+        }
+    }
+    result
+}
+
 // === impl RollingWriter ===
 
 impl io::Write for RollingWriter<'_> {
@@ -547,7 +700,228 @@ fn create_writer(directory: &Path, filename: &str) -> Result<File, InitError> {
     new_file.map_err(InitError::ctx("failed to create initial log file"))
 }
 
-fn get_current_time(time: SystemTime) -> OffsetDateTime {
-    OffsetDateTime::from(time)
-        .to_offset(UtcOffset::local_offset_at(OffsetDateTime::now_utc()).unwrap())
+// 日志文件的创建时间，不支持 birth time 的平台/文件系统上退回到修改时间
+fn file_time(file: &File) -> Result<SystemTime, InitError> {
+    let metadata = file
+        .metadata()
+        .map_err(InitError::ctx("failed to read log file metadata"))?;
+    created_or_modified(metadata.created(), || metadata.modified())
+        .map_err(InitError::ctx("failed to read log file creation time"))
+}
+
+fn created_or_modified(
+    created: io::Result<SystemTime>,
+    modified: impl FnOnce() -> io::Result<SystemTime>,
+) -> io::Result<SystemTime> {
+    created.or_else(|_| modified())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use time::macros::datetime;
+
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("zenrc_log_{}_{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        dir
+    }
+
+    // 将 writer 的创建时间改为 date 后执行一次滚动
+    fn rotate(appender: &RollingFileAppender, target: &str, date: OffsetDateTime) {
+        let meta = &appender.writers[target];
+        *meta.crate_time.write() = date;
+        let start = appender.rotation.next_date(&date, true).unwrap();
+        meta.refresh_writer(&mut meta.writer.write(), &appender.date_format, start);
+    }
+
+    #[test]
+    fn prune_ignores_sibling_writer_files() {
+        let dir = temp_dir("prune_sibling");
+        let appender = Builder::new()
+            .rotation(Rotation::DAILY)
+            .filename("app")
+            .filter("debug", "app.debug")
+            .max_log_files(2)
+            .build(&dir)
+            .unwrap();
+
+        rotate(&appender, "debug", datetime!(2024-01-01 0:00 UTC));
+        rotate(&appender, "debug", datetime!(2024-01-02 0:00 UTC));
+        rotate(&appender, "default", datetime!(2024-01-01 0:00 UTC));
+        rotate(&appender, "default", datetime!(2024-01-02 0:00 UTC));
+        rotate(&appender, "default", datetime!(2024-01-03 0:00 UTC));
+
+        assert!(dir.join("app.debug").exists());
+        assert!(dir.join("app.debug.2024-01-02").exists());
+        assert!(dir.join("app").exists());
+        assert!(dir.join("app.2024-01-03").exists());
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn max_files_includes_active_file() {
+        let dir = temp_dir("max_files");
+        let appender = Builder::new()
+            .rotation(Rotation::DAILY)
+            .filename("app")
+            .max_log_files(3)
+            .build(&dir)
+            .unwrap();
+
+        for day in 1..=5 {
+            let date = datetime!(2024-01-01 0:00 UTC) + Duration::days(day);
+            rotate(&appender, "default", date);
+        }
+
+        let mut files = fs::read_dir(&dir)
+            .unwrap()
+            .map(|entry| entry.unwrap().file_name().into_string().unwrap())
+            .collect::<Vec<_>>();
+        files.sort();
+        assert_eq!(files, ["app", "app.2024-01-05", "app.2024-01-06"]);
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn falls_back_to_modified_time_without_birth_time() {
+        let unsupported = || Err(io::Error::from(io::ErrorKind::Unsupported));
+        let modified = SystemTime::UNIX_EPOCH + std::time::Duration::from_secs(60);
+
+        assert_eq!(created_or_modified(unsupported(), || Ok(modified)).unwrap(), modified);
+        assert!(created_or_modified(unsupported(), unsupported).is_err());
+    }
+
+    #[test]
+    fn failed_rename_keeps_writing_current_file() {
+        let dir = temp_dir("rename_failure");
+        let appender = Builder::new()
+            .rotation(Rotation::DAILY)
+            .filename("app")
+            .build(&dir)
+            .unwrap();
+        // 滚动目标被非空目录占用，rename 必然失败
+        fs::create_dir_all(dir.join("app.2024-01-01").join("occupied")).unwrap();
+
+        rotate(&appender, "default", datetime!(2024-01-01 0:00 UTC));
+
+        let meta = &appender.writers["default"];
+        let now = datetime!(2024-01-02 12:00 UTC);
+        assert!(meta.next_period(&appender.rotation, true, now).is_none());
+        RollingWriter(meta.writer.read()).write_all(b"still logging").unwrap();
+        assert_eq!(fs::read(dir.join("app")).unwrap(), b"still logging");
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn rotated_file_uses_configured_suffix() {
+        let dir = temp_dir("suffix");
+        let appender = Builder::new()
+            .rotation(Rotation::DAILY)
+            .filename("app")
+            .suffix("log")
+            .max_log_files(2)
+            .build(&dir)
+            .unwrap();
+
+        rotate(&appender, "default", datetime!(2024-01-01 0:00 UTC));
+        rotate(&appender, "default", datetime!(2024-01-02 0:00 UTC));
+
+        let mut files = fs::read_dir(&dir)
+            .unwrap()
+            .map(|entry| entry.unwrap().file_name().into_string().unwrap())
+            .collect::<Vec<_>>();
+        files.sort();
+        assert_eq!(files, ["app-2024-01-02.log", "app.log"]);
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn make_writer_rotates_default_writer() {
+        use tracing_subscriber::fmt::writer::MakeWriter;
+
+        let dir = temp_dir("make_writer");
+        let appender = Builder::new()
+            .rotation(Rotation::DAILY)
+            .filename("app")
+            .build(&dir)
+            .unwrap();
+        appender.make_writer().write_all(b"old").unwrap();
+        *appender.writers["default"].crate_time.write() = datetime!(2024-01-01 0:00 UTC);
+
+        appender.make_writer().write_all(b"new").unwrap();
+
+        assert_eq!(fs::read(dir.join("app.2024-01-01")).unwrap(), b"old");
+        assert_eq!(fs::read(dir.join("app")).unwrap(), b"new");
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn flush_persists_written_bytes() {
+        use tracing_subscriber::fmt::writer::MakeWriter;
+
+        let dir = temp_dir("flush");
+        let appender = Builder::new().filename("app").build(&dir).unwrap();
+        let guard = appender.flush_guard();
+
+        appender.make_writer().write_all(b"first").unwrap();
+        appender.flush().unwrap();
+        assert_eq!(fs::read(dir.join("app")).unwrap(), b"first");
+
+        appender.make_writer().write_all(b" second").unwrap();
+        drop(appender);
+        drop(guard);
+        assert_eq!(fs::read(dir.join("app")).unwrap(), b"first second");
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn daily_rollover_aligns_to_midnight() {
+        let created = datetime!(2024-01-01 14:37 +8);
+
+        let next = Rotation::DAILY.next_date(&created, true);
+        assert_eq!(next, Some(datetime!(2024-01-02 0:00 +8)));
+        let next = Rotation::DAILY.next_date(&created, false);
+        assert_eq!(next, Some(datetime!(2024-01-02 14:37 +8)));
+
+        let created = datetime!(2024-01-31 14:37 +8);
+        let next = Rotation::MONTHLY.next_date(&created, true);
+        assert_eq!(next, Some(datetime!(2024-02-01 0:00 +8)));
+        let next = Rotation::MONTHLY.next_date(&created, false);
+        assert_eq!(next, Some(datetime!(2024-02-29 14:37 +8)));
+    }
+
+    #[test]
+    fn rolls_over_once_per_period() {
+        let dir = temp_dir("once_per_period");
+        let appender = Builder::new()
+            .rotation(Rotation::DAILY)
+            .filename("app")
+            .build(&dir)
+            .unwrap();
+        *appender.writers["default"].crate_time.write() = datetime!(2024-01-01 10:00 UTC);
+
+        let ticks = [
+            (datetime!(2024-01-01 12:00 UTC), "a"),
+            (datetime!(2024-01-02 0:00 UTC), "b"),
+            (datetime!(2024-01-02 0:00 UTC), "c"),
+            (datetime!(2024-01-02 8:00 UTC), "d"),
+            (datetime!(2024-01-03 0:00:01 UTC), "e"),
+            (datetime!(2024-01-03 0:00:02 UTC), "f"),
+        ];
+        for (now, text) in ticks {
+            appender.writer_at("default", now).write_all(text.as_bytes()).unwrap();
+        }
+
+        let mut files = fs::read_dir(&dir)
+            .unwrap()
+            .map(|entry| entry.unwrap().file_name().into_string().unwrap())
+            .collect::<Vec<_>>();
+        files.sort();
+        assert_eq!(files, ["app", "app.2024-01-01", "app.2024-01-02"]);
+        assert_eq!(fs::read(dir.join("app.2024-01-01")).unwrap(), b"a");
+        assert_eq!(fs::read(dir.join("app.2024-01-02")).unwrap(), b"bcd");
+        assert_eq!(fs::read(dir.join("app")).unwrap(), b"ef");
+        fs::remove_dir_all(&dir).unwrap();
+    }
 }
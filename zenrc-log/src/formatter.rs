@@ -1,12 +1,133 @@
-use time::OffsetDateTime;
+use std::borrow::Cow;
+use std::fmt;
+use std::time::Instant;
+
 use time::macros::format_description;
+use tracing::field::{Field, Visit};
+use tracing::span::Id;
 use tracing::{Event, Subscriber};
 use tracing_subscriber::fmt::format::{self, FormatEvent, FormatFields};
 use tracing_subscriber::fmt::{FmtContext, FormattedFields};
+use tracing_subscriber::layer::{Context, Layer};
 use tracing_subscriber::registry::LookupSpan;
 
+use crate::local_time;
+
 // 自定义日志格式化器
-pub struct LogFormatter;
+#[derive(Debug, Clone)]
+pub struct LogFormatter {
+    with_pid: bool,
+    with_thread_name: bool,
+    with_source_location: bool,
+    with_target: bool,
+    pretty: bool,
+    line_terminator: Cow<'static, str>,
+    with_span_timings: bool,
+    redacted: Vec<String>,
+    prefix_field: Option<String>,
+}
+
+impl LogFormatter {
+    pub const fn new() -> Self {
+        Self {
+            with_pid: false,
+            with_thread_name: false,
+            with_source_location: true,
+            with_target: true,
+            pretty: false,
+            line_terminator: Cow::Borrowed("\n"),
+            with_span_timings: false,
+            redacted: Vec::new(),
+            prefix_field: None,
+        }
+    }
+
+    /// 在日志级别后输出进程号 `[pid:1234]`，默认关闭
+    pub fn with_pid(self, with_pid: bool) -> Self {
+        Self { with_pid, ..self }
+    }
+
+    /// 在日志级别后输出线程名 `[thread:main]`，未命名线程输出线程 id，默认关闭
+    pub fn with_thread_name(self, with_thread_name: bool) -> Self {
+        Self {
+            with_thread_name,
+            ..self
+        }
+    }
+
+    /// 在行尾输出源码位置 `[file:line]`，默认开启；生产环境可关闭以免暴露源码路径
+    pub fn with_source_location(self, with_source_location: bool) -> Self {
+        Self {
+            with_source_location,
+            ..self
+        }
+    }
+
+    /// 在日志级别后输出 target `[target]`，默认开启
+    pub fn with_target(self, with_target: bool) -> Self {
+        Self {
+            with_target,
+            ..self
+        }
+    }
+
+    /// 多行格式：首行输出时间戳、级别、span 和消息，其余字段各占一行并缩进，默认关闭
+    ///
+    /// 适合开发时查看字段较多的事件。
+    pub fn pretty(self, pretty: bool) -> Self {
+        Self { pretty, ..self }
+    }
+
+    /// 每条日志结束时输出的分隔符，默认 `"\n"`
+    ///
+    /// 例如 RFC 7464 JSON 文本序列使用 `"\x1e"`。多行格式下只替换整条日志末尾的分隔符。
+    pub fn with_line_terminator(self, terminator: &str) -> Self {
+        Self {
+            line_terminator: Cow::Owned(terminator.to_string()),
+            ..self
+        }
+    }
+
+    /// 在每个 span 后输出其自首次进入以来经过的时间 `elapsed=1.234ms`，默认关闭
+    ///
+    /// 需要订阅器中包含 [`SpanTimingLayer`]，[`SubscriberBuilder`](crate::SubscriberBuilder)
+    /// 构建的订阅器已默认包含。
+    pub fn with_span_timings(self, with_span_timings: bool) -> Self {
+        Self {
+            with_span_timings,
+            ..self
+        }
+    }
+
+    /// 隐藏指定名字的事件字段的值，输出为 `name=<redacted>`，用于密码、令牌等敏感字段
+    ///
+    /// 可多次调用，名字累加。只作用于事件字段，span 字段由订阅器预先格式化，不受影响。
+    pub fn redact(mut self, names: &[&str]) -> Self {
+        self.redacted.extend(names.iter().map(|name| name.to_string()));
+        self
+    }
+
+    /// 把名为 name 的事件字段提到行首，在线程名之后输出 `[name:value]`，并从字段列表中移除
+    ///
+    /// 事件没有该字段时不输出。该字段同时被 [`redact`](Self::redact) 时输出 `[name:<redacted>]`。
+    pub fn with_prefix_field(self, name: &str) -> Self {
+        Self {
+            prefix_field: Some(name.to_string()),
+            ..self
+        }
+    }
+
+    // 是否需要自行收集事件字段，而不是交给订阅器的字段格式化器
+    fn collects_fields(&self) -> bool {
+        self.pretty || !self.redacted.is_empty() || self.prefix_field.is_some()
+    }
+}
+
+impl Default for LogFormatter {
+    fn default() -> Self {
+        Self::new()
+    }
+}
 
 impl<S, N> FormatEvent<S, N> for LogFormatter
 where
@@ -17,15 +138,36 @@ where
         let metadata = event.metadata();
 
         // 打印时间戳
-        let now = OffsetDateTime::now_local().expect("Failed to get local time");
+        let now = local_time::now_local();
         let format = format_description!("[year]-[month]-[day] [hour]:[minute]:[second].[subsecond digits:3]");
         write!(writer, "[{}] ", now.format(&format).expect("Failed to format time"))?;
 
-        // 打印日志级别、target、文件和行号
-        let file = metadata.file().unwrap_or("unknown");
-        let line = metadata.line().map(|l| l.to_string()).unwrap_or_default();
-
+        // 打印日志级别和 target
         write!(&mut writer, "[{}] ", metadata.level())?;
+        if self.with_target {
+            write!(writer, "[{}] ", metadata.target())?;
+        }
+
+        // 需要隐藏或提取字段时先收集事件字段，前缀字段在线程名之后输出
+        let mut fields = EventFields::new(self);
+        if self.collects_fields() {
+            event.record(&mut fields);
+        }
+
+        // 打印进程号和线程名
+        if self.with_pid {
+            write!(writer, "[pid:{}] ", std::process::id())?;
+        }
+        if self.with_thread_name {
+            let thread = std::thread::current();
+            match thread.name() {
+                Some(name) => write!(writer, "[thread:{}] ", name)?,
+                None => write!(writer, "[thread:{:?}] ", thread.id())?,
+            }
+        }
+        if let (Some(name), Some(value)) = (&self.prefix_field, &fields.prefix) {
+            write!(writer, "[{}:{}] ", name, value)?;
+        }
 
         // 打印 span 信息
         if let Some(scope) = ctx.event_scope() {
@@ -40,6 +182,11 @@ where
                         write!(writer, "{{{}}}", fields)?;
                     }
                 }
+                if self.with_span_timings
+                    && let Some(SpanEnteredAt(entered)) = ext.get::<SpanEnteredAt>()
+                {
+                    write!(writer, " elapsed={:.3?}", entered.elapsed())?;
+                }
 
                 // 除了最后一个 span，其余加 "/ "
                 if i < spans.len() - 1 {
@@ -50,10 +197,251 @@ where
         }
         // write!(writer, ": ")?;
 
-        // 打印事件字段
-        ctx.field_format().format_fields(writer.by_ref(), event)?;
+        // 多行格式下首行只打印消息，字段在位置信息之后逐行输出
+        if self.pretty {
+            write!(writer, "{}", fields.message)?;
+        } else if self.collects_fields() {
+            // 与默认字段格式一致：消息在前，其余字段以空格分隔输出为 name=value
+            let message = Some(fields.message.as_str()).filter(|m| !m.is_empty());
+            let pairs = fields.fields.iter().map(|(name, value)| format!("{}={}", name, value));
+            let line: Vec<_> = message.map(str::to_string).into_iter().chain(pairs).collect();
+            write!(writer, "{}", line.join(" "))?;
+        } else {
+            // 打印事件字段
+            ctx.field_format().format_fields(writer.by_ref(), event)?;
+        }
+
+        // 打印文件和行号
+        if self.with_source_location {
+            let file = metadata.file().unwrap_or("unknown");
+            let line = metadata.line().map(|l| l.to_string()).unwrap_or_default();
+            write!(writer, " [{}:{}]", file, line)?;
+        }
+        if self.pretty {
+            for (name, value) in &fields.fields {
+                write!(writer, "\n    {}: {}", name, value)?;
+            }
+        }
+        write!(writer, "{}", self.line_terminator)
+    }
+}
+
+// span 首次进入的时间，由 SpanTimingLayer 写入 span 扩展
+struct SpanEnteredAt(Instant);
+
+/// 记录 span 首次进入时间的 [`Layer`]，供 [`LogFormatter::with_span_timings`] 使用
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SpanTimingLayer;
+
+impl<S> Layer<S> for SpanTimingLayer
+where
+    S: Subscriber + for<'a> LookupSpan<'a>,
+{
+    fn on_enter(&self, id: &Id, ctx: Context<'_, S>) {
+        let Some(span) = ctx.span(id) else {
+            return;
+        };
+        let mut ext = span.extensions_mut();
+        if ext.get_mut::<SpanEnteredAt>().is_none() {
+            ext.insert(SpanEnteredAt(Instant::now()));
+        }
+    }
+}
+
+// 收集事件字段：message 和前缀字段单独存放，被隐藏的字段值替换为 <redacted>
+struct EventFields<'a> {
+    formatter: &'a LogFormatter,
+    message: String,
+    prefix: Option<String>,
+    fields: Vec<(&'static str, String)>,
+}
+
+impl<'a> EventFields<'a> {
+    fn new(formatter: &'a LogFormatter) -> Self {
+        Self {
+            formatter,
+            message: String::new(),
+            prefix: None,
+            fields: Vec::new(),
+        }
+    }
+
+    // plain 用于消息、前缀和多行格式，quoted 用于单行格式（字符串带引号，与默认字段格式一致）
+    fn record(&mut self, field: &Field, plain: String, quoted: String) {
+        let name = field.name();
+        if name == "message" {
+            self.message = plain;
+            return;
+        }
+        let redacted = self.formatter.redacted.iter().any(|r| r == name);
+        let (plain, quoted) = if redacted {
+            ("<redacted>".to_string(), "<redacted>".to_string())
+        } else {
+            (plain, quoted)
+        };
+        if self.formatter.prefix_field.as_deref() == Some(name) {
+            self.prefix = Some(plain);
+        } else if self.formatter.pretty {
+            self.fields.push((name, plain));
+        } else {
+            self.fields.push((name, quoted));
+        }
+    }
+}
+
+impl Visit for EventFields<'_> {
+    fn record_str(&mut self, field: &Field, value: &str) {
+        self.record(field, value.to_string(), format!("{:?}", value));
+    }
+
+    fn record_debug(&mut self, field: &Field, value: &dyn fmt::Debug) {
+        let value = format!("{:?}", value);
+        self.record(field, value.clone(), value);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::writer::SharedBufferWriter;
+
+    // 用给定的格式化器输出一条日志并返回结果
+    fn format_one(formatter: LogFormatter) -> String {
+        format_with(formatter, || tracing::info!("hello"))
+    }
+
+    // 用给定的格式化器执行 log 并返回输出
+    fn format_with(formatter: LogFormatter, log: impl FnOnce()) -> String {
+        let buffer = SharedBufferWriter::new();
+        let subscriber = tracing_subscriber::fmt()
+            .with_ansi(false)
+            .event_format(formatter)
+            .with_writer(buffer.clone())
+            .finish();
+        tracing::subscriber::with_default(subscriber, log);
+        String::from_utf8(buffer.contents()).unwrap()
+    }
+
+    #[test]
+    fn pid_and_thread_name_are_opt_in() {
+        let pid = format!("[pid:{}]", std::process::id());
+
+        let line = format_one(LogFormatter::new());
+        assert!(!line.contains(&pid));
+        assert!(!line.contains("[thread:"));
+
+        let formatter = LogFormatter::new()
+            .with_target(false)
+            .with_pid(true)
+            .with_thread_name(true);
+        let line = format_one(formatter);
+        let thread = std::thread::current();
+        assert!(line.contains(&format!("[INFO] {} [thread:{}] ", pid, thread.name().unwrap())));
+    }
+
+    #[test]
+    fn source_location_can_be_disabled() {
+        let location = format!("[{}:", file!());
+
+        let line = format_one(LogFormatter::default());
+        assert!(line.contains(&location));
+
+        let line = format_one(LogFormatter::new().with_source_location(false));
+        assert!(!line.contains(&location));
+        assert!(line.ends_with("hello\n"));
+    }
+
+    #[test]
+    fn target_follows_level() {
+        let target = module_path!();
+
+        let line = format_one(LogFormatter::default());
+        assert!(line.contains(&format!("[INFO] [{}] ", target)));
+
+        let line = format_one(LogFormatter::new().with_target(false));
+        assert!(!line.contains(&format!("[{}]", target)));
+    }
+
+    #[test]
+    fn pretty_puts_each_field_on_its_own_line() {
+        let formatter = LogFormatter::new().pretty(true).with_source_location(false);
+        let output = format_with(formatter, || {
+            tracing::info!(x = 1, y = "two", z = true, "moved");
+        });
+
+        let lines: Vec<_> = output.lines().collect();
+        assert_eq!(lines.len(), 4);
+        assert!(lines[0].ends_with("[INFO] [zenrc_log::formatter::tests] moved"));
+        assert_eq!(lines[1..], ["    x: 1", "    y: two", "    z: true"]);
+    }
+
+    #[test]
+    fn line_terminator_ends_each_record() {
+        let formatter = LogFormatter::new()
+            .with_source_location(false)
+            .with_line_terminator("\x1e");
+        let output = format_with(formatter, || {
+            tracing::info!("first");
+            tracing::info!("second");
+        });
+
+        let records: Vec<_> = output.split_terminator('\x1e').collect();
+        assert_eq!(records.len(), 2);
+        assert!(records[0].ends_with("first"));
+        assert!(records[1].ends_with("second"));
+        assert!(!output.contains('\n'));
+    }
+
+    #[test]
+    fn redacted_fields_hide_values() {
+        let formatter = LogFormatter::new()
+            .with_source_location(false)
+            .redact(&["token", "password"]);
+        let line = format_with(formatter, || {
+            tracing::info!(user = "ada", token = "s3cr3t", "login");
+        });
+        assert!(line.ends_with("login user=\"ada\" token=<redacted>\n"), "{}", line);
+        assert!(!line.contains("s3cr3t"));
+
+        // 未配置时与默认字段格式一致
+        let plain = format_with(LogFormatter::new().with_source_location(false), || {
+            tracing::info!(user = "ada", token = "s3cr3t", "login");
+        });
+        assert!(plain.ends_with("login user=\"ada\" token=\"s3cr3t\"\n"), "{}", plain);
+    }
+
+    #[test]
+    fn prefix_field_moves_to_line_prefix() {
+        let formatter = LogFormatter::new()
+            .with_target(false)
+            .with_source_location(false)
+            .with_prefix_field("robot");
+        let line = format_with(formatter, || {
+            tracing::info!(robot = "r2", speed = 3, "moving");
+        });
+        assert!(line.ends_with("[INFO] [robot:r2] moving speed=3\n"), "{}", line);
+    }
+
+    #[test]
+    fn span_timings_report_elapsed_time() {
+        use tracing_subscriber::layer::SubscriberExt;
+
+        let buffer = SharedBufferWriter::new();
+        let subscriber = tracing_subscriber::fmt()
+            .event_format(LogFormatter::new().with_span_timings(true))
+            .with_writer(buffer.clone())
+            .finish()
+            .with(SpanTimingLayer);
+
+        tracing::subscriber::with_default(subscriber, || {
+            let _span = tracing::info_span!("plan").entered();
+            std::thread::sleep(std::time::Duration::from_millis(20));
+            tracing::info!("planned");
+        });
 
-        write!(writer, " [{}:{}]", file, line)?;
-        writeln!(writer)
+        let line = String::from_utf8(buffer.contents()).unwrap();
+        let elapsed = line.split("[plan elapsed=").nth(1).unwrap();
+        let elapsed = elapsed.split("ms]").next().unwrap();
+        assert!(elapsed.parse::<f64>().unwrap() >= 20.0);
     }
 }
@@ -1,15 +1,25 @@
 pub mod appender;
 pub mod formatter;
+mod local_time;
+pub mod rate_limit;
+pub mod writer;
+use std::backtrace::{Backtrace, BacktraceStatus};
 use std::path::Path;
 
-use appender::builder::{RollingFileAppender, Rotation};
+use appender::builder::{FlushGuard, InitError, RollingFileAppender, Rotation};
+use tracing::Subscriber;
+use tracing_subscriber::filter::LevelFilter;
 use tracing_subscriber::fmt;
+use tracing_subscriber::fmt::MakeWriter;
 use tracing_subscriber::fmt::format::FormatEvent;
-use tracing_subscriber::layer::SubscriberExt;
+use tracing_subscriber::fmt::writer::BoxMakeWriter;
+use tracing_subscriber::layer::{Identity, Layer, SubscriberExt};
 use tracing_subscriber::registry::Registry;
+use tracing_subscriber::reload;
 use tracing_subscriber::util::SubscriberInitExt;
 
-use crate::formatter::LogFormatter;
+use crate::formatter::{LogFormatter, SpanTimingLayer};
+use crate::rate_limit::RateLimitLayer;
 
 pub use tracing::Level;
 pub use tracing::{debug, error, info, trace, warn};
@@ -32,20 +42,70 @@ impl Into<Rotation> for Period {
         }
     }
 }
+/// 运行时调整日志级别的句柄，由 [`SubscriberBuilder::init_reloadable`] 返回
+///
+/// 由 `init_reloadable` 返回的句柄同时持有刷新守卫，析构时将日志文件落盘，
+/// 应在 `main` 中绑定到非 `_` 的变量。
+pub struct LevelHandle {
+    reload: Box<dyn Fn(LevelFilter) -> Result<(), reload::Error> + Send + Sync>,
+    _guard: Option<FlushGuard>,
+}
+
+impl LevelHandle {
+    /// 更新当前生效的日志级别，之后的日志事件立即按新级别过滤
+    pub fn set_level(&self, level: Level) -> Result<(), reload::Error> {
+        (self.reload)(LevelFilter::from_level(level))
+    }
+}
+
+/// 安装 panic 钩子，把 panic 信息以 `error` 级别记录到日志中
+///
+/// 记录 panic 消息、位置和线程名，设置 `RUST_BACKTRACE` 时附带调用栈；
+/// 记录后调用之前的钩子，默认钩子仍会输出到 stderr。应在日志订阅器安装后调用。
+pub fn install_panic_hook() {
+    let previous = std::panic::take_hook();
+    std::panic::set_hook(Box::new(move |info| {
+        let payload = info.payload();
+        let message = payload
+            .downcast_ref::<&str>()
+            .copied()
+            .or_else(|| payload.downcast_ref::<String>().map(String::as_str))
+            .unwrap_or("Box<dyn Any>");
+        let location = info
+            .location()
+            .map(|location| location.to_string())
+            .unwrap_or_default();
+        let thread = std::thread::current();
+        let thread = thread.name().unwrap_or("<unnamed>");
+
+        let backtrace = Backtrace::capture();
+        if backtrace.status() == BacktraceStatus::Captured {
+            error!(target: "panic", thread, location, "{}\n{}", message, backtrace);
+        } else {
+            error!(target: "panic", thread, location, "{}", message);
+        }
+        previous(info);
+    }));
+}
+
 pub struct SubscriberBuilder<E = LogFormatter> {
     event_formatter: E,
     level: Level,
     directory: String,
     appender_builder: appender::builder::Builder,
+    writer: Option<BoxMakeWriter>,
+    rate_limit: Option<usize>,
 }
 
 impl SubscriberBuilder {
     pub fn new() -> Self {
         SubscriberBuilder {
-            event_formatter: LogFormatter,
+            event_formatter: LogFormatter::new(),
             level: Level::INFO,
             directory: String::new(),
             appender_builder: RollingFileAppender::builder(),
+            writer: None,
+            rate_limit: None,
         }
     }
 }
@@ -63,13 +123,27 @@ where
     pub fn with_level(self, level: Level) -> Self {
         SubscriberBuilder { level, ..self }
     }
+    /// 同时设置日志目录和文件名，等价于依次调用 [`with_directory`](Self::with_directory)
+    /// 和 [`with_filename`](Self::with_filename)
+    ///
+    /// path 必须包含文件名，否则 panic。
     pub fn with_path(self, path: impl Into<String>) -> Self {
         let path = path.into();
         let file_name = Path::new(&path).file_name().unwrap().to_str().unwrap();
         let directory = Path::new(&path).parent().unwrap().to_str().unwrap();
+        self.with_directory(directory).with_filename(file_name)
+    }
+    /// 设置日志目录，目录不存在时自动创建
+    pub fn with_directory(self, directory: impl Into<String>) -> Self {
         SubscriberBuilder {
             directory: directory.into(),
-            appender_builder: self.appender_builder.filename(file_name),
+            ..self
+        }
+    }
+    /// 设置日志文件名，即滚动文件名的前缀
+    pub fn with_filename(self, filename: impl Into<String>) -> Self {
+        SubscriberBuilder {
+            appender_builder: self.appender_builder.filename(filename),
             ..self
         }
     }
@@ -85,6 +159,33 @@ where
             ..self
         }
     }
+    pub fn with_suffix(self, suffix: impl Into<String>) -> Self {
+        SubscriberBuilder {
+            appender_builder: self.appender_builder.suffix(suffix),
+            ..self
+        }
+    }
+    /// 使用自定义输出代替标准输出和日志文件，设置后日志路径相关配置不再生效
+    ///
+    /// 测试中可配合 [`SharedBufferWriter`](writer::SharedBufferWriter) 捕获日志输出。
+    pub fn with_writer<W>(self, writer: W) -> Self
+    where
+        W: for<'a> MakeWriter<'a> + Send + Sync + 'static,
+    {
+        SubscriberBuilder {
+            writer: Some(BoxMakeWriter::new(writer)),
+            ..self
+        }
+    }
+    /// 每个日志调用点每秒最多输出 max_per_sec 条，超出的被丢弃并定期输出丢弃数量的摘要
+    ///
+    /// 用于紧密循环中的日志，详见 [`RateLimitLayer`]。
+    pub fn with_rate_limit(self, max_per_sec: usize) -> Self {
+        SubscriberBuilder {
+            rate_limit: Some(max_per_sec),
+            ..self
+        }
+    }
     pub fn with_filter(
         self,
         target: impl Into<String>,
@@ -98,33 +199,238 @@ where
         }
     }
 
+    /// 安装全局日志订阅器；日志文件创建失败或全局订阅器已存在时返回错误
+    pub fn try_init(self) -> Result<(), InitError> {
+        self.install().map(drop)
+    }
+
+    /// 安装全局日志订阅器，失败时 panic
     pub fn init(self) {
-        if self.directory.is_empty() {
-            let filter = tracing_subscriber::filter::LevelFilter::from_level(self.level);
-            let layer = fmt::layer()
-                .event_format(self.event_formatter)
-                .with_ansi(false);
-            tracing_subscriber::registry()
-                .with(layer)
-                .with(filter)
-                .init();
-        } else {
-            // let file_name = Path::new(&self.path).file_name().unwrap().to_str().unwrap();
-            // let dir = Path::new(&self.path).parent().unwrap().to_str().unwrap();
-            let file_appender = self
-                .appender_builder
-                .build(self.directory)
-                .expect("failed to initialize rolling file appender");
-
-            let layer = fmt::layer()
-                .event_format(self.event_formatter)
-                .with_writer(file_appender)
-                .with_ansi(false);
-            let filter = tracing_subscriber::filter::LevelFilter::from_level(self.level);
-            tracing_subscriber::registry()
-                .with(layer)
-                .with(filter)
-                .init();
+        self.try_init().expect("failed to initialize logging")
+    }
+
+    /// 安装全局日志订阅器并返回刷新守卫，守卫析构时将日志文件落盘，失败时 panic
+    ///
+    /// 守卫应在 `main` 中绑定到非 `_` 的变量；未设置日志路径时守卫不做任何事。
+    pub fn init_with_guard(self) -> FlushGuard {
+        self.install().expect("failed to initialize logging")
+    }
+
+    /// 安装全局日志订阅器并返回级别句柄，用于运行时调整日志级别，失败时 panic
+    ///
+    /// 句柄持有刷新守卫，见 [`LevelHandle`]。
+    pub fn init_reloadable(self) -> LevelHandle {
+        let (subscriber, guard, level) = self.build().expect("failed to initialize logging");
+        subscriber.try_init().expect("failed to initialize logging");
+        LevelHandle {
+            _guard: Some(guard),
+            ..level
         }
     }
+
+    fn install(self) -> Result<FlushGuard, InitError> {
+        let (subscriber, guard, _) = self.build()?;
+        subscriber.try_init()?;
+        Ok(guard)
+    }
+
+    fn build(
+        self,
+    ) -> Result<(impl Subscriber + Send + Sync + 'static, FlushGuard, LevelHandle), InitError> {
+        // 安装时通常还未启动其他线程，先取得一次本地时区偏移，之后读取失败时回退到它
+        local_time::local_offset();
+        let (filter, handle) = reload::Layer::new(LevelFilter::from_level(self.level));
+        let level = LevelHandle {
+            reload: Box::new(move |filter| handle.reload(filter)),
+            _guard: None,
+        };
+        let (writer, guard) = if let Some(writer) = self.writer {
+            (writer, FlushGuard::default())
+        } else if self.directory.is_empty() {
+            (BoxMakeWriter::new(std::io::stdout), FlushGuard::default())
+        } else {
+            let file_appender = self.appender_builder.build(self.directory)?;
+            let guard = file_appender.flush_guard();
+            (BoxMakeWriter::new(file_appender), guard)
+        };
+        let layer = fmt::layer()
+            .event_format(self.event_formatter)
+            .with_ansi(false)
+            .with_writer(writer);
+        // Option<Layer> 不会转发 on_register_dispatch，限流层需要它分发摘要，因此用 Box 包装
+        let rate_limit: Box<dyn Layer<_> + Send + Sync> = match self.rate_limit {
+            Some(max_per_sec) => Box::new(RateLimitLayer::new(max_per_sec)),
+            None => Box::new(Identity::new()),
+        };
+        let subscriber = tracing_subscriber::registry()
+            .with(layer)
+            .with(SpanTimingLayer)
+            .with(filter)
+            .with(rate_limit);
+        Ok((subscriber, guard, level))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn try_init_twice_returns_error() {
+        assert!(SubscriberBuilder::new().try_init().is_ok());
+        assert!(SubscriberBuilder::new().try_init().is_err());
+    }
+
+    #[test]
+    fn with_writer_captures_output() {
+        let buffer = writer::SharedBufferWriter::new();
+        let (subscriber, _guard, _level) = SubscriberBuilder::new()
+            .with_writer(buffer.clone())
+            .build()
+            .unwrap();
+
+        tracing::subscriber::with_default(subscriber, || info!("captured in memory"));
+        assert!(buffer.to_string_lossy().contains("captured in memory"));
+    }
+
+    #[test]
+    fn rate_limit_drops_bursts_and_reports_summary() {
+        let buffer = writer::SharedBufferWriter::new();
+        let (subscriber, _guard, _level) = SubscriberBuilder::new()
+            .with_writer(buffer.clone())
+            .with_rate_limit(10)
+            .build()
+            .unwrap();
+
+        tracing::subscriber::with_default(subscriber, || {
+            let burst = || {
+                for i in 0..1000 {
+                    info!("tick {}", i);
+                }
+            };
+            burst();
+            // 进入下一个窗口后才输出上一窗口的摘要
+            std::thread::sleep(std::time::Duration::from_millis(1100));
+            burst();
+        });
+
+        let logs = buffer.to_string_lossy();
+        assert_eq!(logs.matches("tick ").count(), 20);
+        assert!(logs.contains("suppressed 990 messages"), "{}", logs);
+    }
+
+    #[test]
+    fn rate_limit_reports_bursts_that_stop() {
+        let buffer = writer::SharedBufferWriter::new();
+        let (subscriber, _guard, _level) = SubscriberBuilder::new()
+            .with_writer(buffer.clone())
+            .with_rate_limit(10)
+            .build()
+            .unwrap();
+
+        tracing::subscriber::with_default(subscriber, || {
+            for i in 0..100 {
+                info!("tick {}", i);
+            }
+            // 刷屏的调用点不再触发，由其他调用点的事件带出摘要
+            std::thread::sleep(std::time::Duration::from_millis(1100));
+            info!("heartbeat");
+        });
+
+        let logs = buffer.to_string_lossy();
+        assert_eq!(logs.matches("tick ").count(), 10);
+        assert!(logs.contains("suppressed 90 messages"), "{}", logs);
+        assert!(logs.contains("heartbeat"), "{}", logs);
+    }
+
+    #[test]
+    fn rate_limit_summary_respects_level_filter() {
+        let buffer = writer::SharedBufferWriter::new();
+        let (subscriber, _guard, level) = SubscriberBuilder::new()
+            .with_writer(buffer.clone())
+            .with_rate_limit(10)
+            .build()
+            .unwrap();
+
+        tracing::subscriber::with_default(subscriber, || {
+            for i in 0..100 {
+                info!("tick {}", i);
+            }
+            level.set_level(Level::ERROR).unwrap();
+            std::thread::sleep(std::time::Duration::from_millis(1100));
+            tracing::error!("heartbeat");
+        });
+
+        let logs = buffer.to_string_lossy();
+        assert!(logs.contains("heartbeat"), "{}", logs);
+        assert!(!logs.contains("suppressed"), "{}", logs);
+    }
+
+    #[test]
+    fn panic_hook_logs_panics() {
+        install_panic_hook();
+        let buffer = writer::SharedBufferWriter::new();
+        let (subscriber, _guard, _level) = SubscriberBuilder::new()
+            .with_writer(buffer.clone())
+            .build()
+            .unwrap();
+
+        // 订阅器只对当前线程生效，panic 钩子在 panic 的线程中执行
+        let result = std::thread::Builder::new()
+            .name("worker".to_string())
+            .spawn(move || {
+                tracing::subscriber::with_default(subscriber, || panic!("worker exploded"));
+            })
+            .unwrap()
+            .join();
+
+        assert!(result.is_err());
+        let logs = buffer.to_string_lossy();
+        assert!(logs.contains("[ERROR] [panic]"));
+        assert!(logs.contains("worker exploded"));
+        assert!(logs.contains("thread=\"worker\""));
+    }
+
+    #[test]
+    fn split_setters_create_file_in_directory() {
+        let dir = std::env::temp_dir().join(format!("zenrc_log_split_{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        let (subscriber, guard, _level) = SubscriberBuilder::new()
+            .with_directory(dir.to_str().unwrap())
+            .with_filename("robot.log")
+            .build()
+            .unwrap();
+
+        tracing::subscriber::with_default(subscriber, || info!("split setters"));
+        drop(guard);
+
+        let logs = std::fs::read_to_string(dir.join("robot.log")).unwrap();
+        assert!(logs.contains("split setters"));
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn reloaded_level_applies_to_later_events() {
+        let dir = std::env::temp_dir().join(format!("zenrc_log_reload_{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        let path = dir.join("app.log");
+        let (subscriber, guard, level) = SubscriberBuilder::new()
+            .with_path(path.to_str().unwrap())
+            .build()
+            .unwrap();
+
+        tracing::subscriber::with_default(subscriber, || {
+            info!("info before reload");
+            debug!("debug before reload");
+            level.set_level(Level::DEBUG).unwrap();
+            debug!("debug after reload");
+        });
+        drop(guard);
+
+        let logs = std::fs::read_to_string(&path).unwrap();
+        assert!(logs.contains("info before reload"));
+        assert!(!logs.contains("debug before reload"));
+        assert!(logs.contains("debug after reload"));
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
 }
@@ -0,0 +1,58 @@
+//! 本地时间的统一获取
+//!
+//! time 在多线程程序中出于安全考虑拒绝读取本地时区偏移（`IndeterminateOffset`），
+//! 这里不 panic：回退到最近一次成功取得的偏移，从未成功过时回退到 UTC。
+use std::sync::atomic::{AtomicI32, Ordering};
+use std::time::SystemTime;
+
+use time::error::IndeterminateOffset;
+use time::{OffsetDateTime, UtcOffset};
+
+// 最近一次成功取得的本地偏移（秒），NONE 表示尚未取得过
+const NONE: i32 = i32::MIN;
+static CACHED_OFFSET: AtomicI32 = AtomicI32::new(NONE);
+
+/// 当前的本地时区偏移，无法确定时使用缓存的偏移或 UTC
+pub(crate) fn local_offset() -> UtcOffset {
+    resolve(UtcOffset::current_local_offset())
+}
+
+/// 当前的本地时间，时区偏移的取得方式见 [`local_offset`]
+pub(crate) fn now_local() -> OffsetDateTime {
+    OffsetDateTime::now_utc().to_offset(local_offset())
+}
+
+/// 把系统时间转换为本地时间，时区偏移的取得方式见 [`local_offset`]
+pub(crate) fn to_local(time: SystemTime) -> OffsetDateTime {
+    OffsetDateTime::from(time).to_offset(local_offset())
+}
+
+fn resolve(offset: Result<UtcOffset, IndeterminateOffset>) -> UtcOffset {
+    match offset {
+        Ok(offset) => {
+            CACHED_OFFSET.store(offset.whole_seconds(), Ordering::Relaxed);
+            offset
+        }
+        Err(IndeterminateOffset) => match CACHED_OFFSET.load(Ordering::Relaxed) {
+            NONE => UtcOffset::UTC,
+            seconds => UtcOffset::from_whole_seconds(seconds).unwrap_or(UtcOffset::UTC),
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn indeterminate_offset_falls_back_instead_of_panicking() {
+        // 尚未取得过偏移时回退到 UTC
+        CACHED_OFFSET.store(NONE, Ordering::Relaxed);
+        assert_eq!(resolve(Err(IndeterminateOffset)), UtcOffset::UTC);
+
+        // 取得过偏移后回退到缓存的偏移
+        let east8 = UtcOffset::from_hms(8, 0, 0).unwrap();
+        assert_eq!(resolve(Ok(east8)), east8);
+        assert_eq!(resolve(Err(IndeterminateOffset)), east8);
+    }
+}
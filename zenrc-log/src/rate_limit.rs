@@ -0,0 +1,151 @@
+//! 按调用点限制日志速率，避免紧密循环中的日志刷屏
+use std::collections::HashMap;
+use std::sync::{Mutex, OnceLock};
+use std::time::{Duration, Instant};
+
+use tracing::callsite::{DefaultCallsite, Identifier};
+use tracing::dispatcher::WeakDispatch;
+use tracing::field::{FieldSet, Value};
+use tracing::metadata::Kind;
+use tracing::{Dispatch, Event, Level, Metadata, Subscriber};
+use tracing_subscriber::layer::{Context, Layer};
+
+// 摘要日志的 target，摘要本身不受限流影响
+const SUMMARY_TARGET: &str = "zenrc_log::rate_limit";
+const WINDOW: Duration = Duration::from_secs(1);
+
+// 摘要事件的调用点；tracing 在分发事件期间会屏蔽嵌套的日志宏，因此摘要直接构造事件分发
+static SUMMARY_CALLSITE: DefaultCallsite = DefaultCallsite::new(&SUMMARY_METADATA);
+static SUMMARY_METADATA: Metadata<'static> = Metadata::new(
+    "rate limit summary",
+    SUMMARY_TARGET,
+    Level::WARN,
+    Some(file!()),
+    Some(line!()),
+    Some(module_path!()),
+    FieldSet::new(&["message"], Identifier(&SUMMARY_CALLSITE)),
+    Kind::EVENT,
+);
+
+// 单个调用点在当前一秒窗口内的计数
+struct Window {
+    start: Instant,
+    count: usize,
+    suppressed: usize,
+    // 调用点名称，用于摘要
+    name: &'static str,
+}
+
+impl Window {
+    fn new(start: Instant, name: &'static str) -> Self {
+        Self {
+            start,
+            count: 0,
+            suppressed: 0,
+            name,
+        }
+    }
+
+    // 窗口到期时开始新窗口，返回上一窗口待输出的摘要
+    fn roll(&mut self, now: Instant) -> Option<(usize, &'static str)> {
+        if now.duration_since(self.start) < WINDOW {
+            return None;
+        }
+        let suppressed = std::mem::take(&mut self.suppressed);
+        *self = Self::new(now, self.name);
+        (suppressed > 0).then_some((suppressed, self.name))
+    }
+}
+
+// 所有调用点的窗口
+struct Windows {
+    by_callsite: HashMap<Identifier, Window>,
+    // 上次检查全部窗口的时间
+    last_sweep: Instant,
+}
+
+/// 按调用点限流的 [`Layer`]：每个调用点每秒最多输出 max_per_sec 条事件，多余的事件被丢弃
+///
+/// 某个调用点的窗口结束后，若该窗口内有事件被丢弃，会输出一条 `warn` 级别的摘要
+/// `suppressed N messages from <调用点>`（target 为 `zenrc_log::rate_limit`）。
+/// 摘要在任意调用点的下一条事件到来时输出，不要求刷屏的调用点再次触发；
+/// 摘要同样受订阅器的级别过滤。
+/// 一般通过 [`SubscriberBuilder::with_rate_limit`](crate::SubscriberBuilder::with_rate_limit) 安装。
+pub struct RateLimitLayer {
+    max_per_sec: usize,
+    windows: Mutex<Windows>,
+    // 所在的订阅器，用于分发摘要事件；弱引用避免与订阅器循环引用
+    dispatch: OnceLock<WeakDispatch>,
+}
+
+impl RateLimitLayer {
+    pub fn new(max_per_sec: usize) -> Self {
+        Self {
+            max_per_sec,
+            windows: Mutex::new(Windows {
+                by_callsite: HashMap::new(),
+                last_sweep: Instant::now(),
+            }),
+            dispatch: OnceLock::new(),
+        }
+    }
+
+    fn emit_summary(&self, suppressed: usize, callsite: &str) {
+        let Some(dispatch) = self.dispatch.get().and_then(WeakDispatch::upgrade) else {
+            return;
+        };
+        let fields = SUMMARY_METADATA.fields();
+        let Some(message) = fields.field("message") else {
+            return;
+        };
+        if !dispatch.enabled(&SUMMARY_METADATA) {
+            return;
+        }
+        let text = format!("suppressed {} messages from {}", suppressed, callsite);
+        let values = [(&message, Some(&text.as_str() as &dyn Value))];
+        dispatch.event(&Event::new(&SUMMARY_METADATA, &fields.value_set(&values)));
+    }
+}
+
+impl<S: Subscriber> Layer<S> for RateLimitLayer {
+    fn on_register_dispatch(&self, subscriber: &Dispatch) {
+        let _ = self.dispatch.set(subscriber.downgrade());
+    }
+
+    fn event_enabled(&self, event: &Event<'_>, _ctx: Context<'_, S>) -> bool {
+        let metadata = event.metadata();
+        if metadata.target() == SUMMARY_TARGET {
+            return true;
+        }
+
+        let now = Instant::now();
+        let (enabled, summaries) = {
+            let mut windows = self.windows.lock().unwrap_or_else(|e| e.into_inner());
+            let mut summaries = Vec::new();
+            let window = windows
+                .by_callsite
+                .entry(metadata.callsite())
+                .or_insert_with(|| Window::new(now, metadata.name()));
+            summaries.extend(window.roll(now));
+            let enabled = window.count < self.max_per_sec;
+            if enabled {
+                window.count += 1;
+            } else {
+                window.suppressed += 1;
+            }
+
+            // 刷屏后不再触发的调用点也要输出摘要：每个窗口检查一次全部调用点
+            if now.duration_since(windows.last_sweep) >= WINDOW {
+                windows.last_sweep = now;
+                summaries.extend(windows.by_callsite.values_mut().filter_map(|w| w.roll(now)));
+            }
+            (enabled, summaries)
+        };
+
+        // 摘要会再次经过本 Layer，须在释放锁之后输出
+        for (suppressed, callsite) in summaries {
+            self.emit_summary(suppressed, callsite);
+        }
+        enabled
+    }
+}
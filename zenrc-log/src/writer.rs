@@ -0,0 +1,53 @@
+//! 内存中的日志输出，便于在测试中检查格式化结果
+use std::io;
+use std::sync::{Arc, Mutex};
+
+use tracing_subscriber::fmt::MakeWriter;
+
+/// 把日志写入共享内存缓冲区的 [`MakeWriter`]
+///
+/// 克隆得到的实例共享同一个缓冲区：把一份交给订阅器，另一份留着读取输出。
+#[derive(Debug, Clone, Default)]
+pub struct SharedBufferWriter {
+    buffer: Arc<Mutex<Vec<u8>>>,
+}
+
+impl SharedBufferWriter {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 已写入的全部字节
+    pub fn contents(&self) -> Vec<u8> {
+        self.buffer.lock().unwrap().clone()
+    }
+
+    /// 已写入的内容，非法 UTF-8 按 [`String::from_utf8_lossy`] 替换
+    pub fn to_string_lossy(&self) -> String {
+        String::from_utf8_lossy(&self.buffer.lock().unwrap()).into_owned()
+    }
+
+    /// 清空缓冲区
+    pub fn clear(&self) {
+        self.buffer.lock().unwrap().clear();
+    }
+}
+
+impl io::Write for SharedBufferWriter {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.buffer.lock().unwrap().extend_from_slice(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+impl<'a> MakeWriter<'a> for SharedBufferWriter {
+    type Writer = SharedBufferWriter;
+
+    fn make_writer(&'a self) -> Self::Writer {
+        self.clone()
+    }
+}
@@ -0,0 +1,23 @@
+//! init_reloadable 安装全局订阅器，单独放在一个测试二进制中，避免影响其他测试
+use zenrc_log::{Level, SubscriberBuilder, debug, info};
+
+#[test]
+fn init_reloadable_adjusts_level_and_flushes_on_drop() {
+    let dir = std::env::temp_dir().join(format!("zenrc_log_reloadable_{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    let path = dir.join("app.log");
+    let level = SubscriberBuilder::new().with_path(path.to_str().unwrap()).init_reloadable();
+
+    info!("info before reload");
+    debug!("debug before reload");
+    level.set_level(Level::DEBUG).unwrap();
+    debug!("debug after reload");
+    // 句柄持有刷新守卫，析构时日志落盘
+    drop(level);
+
+    let logs = std::fs::read_to_string(&path).unwrap();
+    assert!(logs.contains("info before reload"), "{}", logs);
+    assert!(!logs.contains("debug before reload"), "{}", logs);
+    assert!(logs.contains("debug after reload"), "{}", logs);
+    std::fs::remove_dir_all(&dir).unwrap();
+}
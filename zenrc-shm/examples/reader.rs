@@ -1,5 +1,4 @@
 use std::io::Cursor;
-use std::slice;
 
 use anyhow::Result;
 use arrow::array::{Array, Float32Array, ListArray, StringArray, UInt32Array, UInt64Array};
@@ -9,13 +8,12 @@ use zenrc_shm::shm::MemoryHandle;
 fn main() -> Result<()> {
     // 共享内存名称
     let name = "/my_shared_mem_arrow";
-    let size: usize = 4096 * 64;
 
     // 打开现有共享内存
-    let mut mem_handle = MemoryHandle::open(name).expect("MemoryHandle::open failed");
+    let mem_handle = MemoryHandle::open(name).expect("MemoryHandle::open failed");
 
-    // 获取数据指针
-    let data = unsafe { slice::from_raw_parts(mem_handle.get_mut_ptr().as_ptr(), size) };
+    // 整段映射内存，长度为映射大小
+    let data = mem_handle.as_slice();
     loop {
         // 使用 Arrow IPC Reader 解析
         let mut reader = StreamReader::try_new(Cursor::new(data), None)?;
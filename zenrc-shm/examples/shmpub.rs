@@ -18,11 +18,12 @@ fn main() {
 
     // 初始化共享内存
     let name = "/my_shared_mem";
-    let size: usize = 4096;
+    let capacity = 10;
+    let size = MpmcRingBuffer::<i32>::required_bytes(capacity);
     let mut data = 1;
 
     let mut mem_handle = MemoryHandle::new(name, size).expect("MemoryHandle::new failed");
-    let ring_buffer = MpmcRingBuffer::<i32>::new(&mut mem_handle, 10).unwrap();
+    let ring_buffer = MpmcRingBuffer::<i32>::new(&mut mem_handle, capacity).unwrap();
 
     // 主循环
     while running.load(Ordering::Acquire) {
@@ -32,6 +33,17 @@ fn main() {
         std::thread::sleep(std::time::Duration::from_millis(100));
     }
 
-    // 退出前自动释放资源（MemoryHandle drop）
-    println!("Gracefully exiting...");
+    // 先通知订阅者关闭并给它们留出离开的时间，订阅者离开后才能销毁缓冲区中共享的锁
+    let detached = mem_handle.begin_shutdown(std::time::Duration::from_secs(2));
+    if detached {
+        drop(ring_buffer);
+        println!("Gracefully exiting...");
+    } else {
+        // 仍挂载的订阅者可能阻塞在通知锁上，不销毁共享的锁，只释放本地句柄
+        std::mem::forget(ring_buffer);
+        println!("Subscribers still attached, exiting anyway");
+    }
+    if let Err(e) = mem_handle.close() {
+        eprintln!("Failed to release shared memory: {}", e);
+    }
 }
@@ -1,15 +1,25 @@
+use std::time::Duration;
+
 use zenrc_shm::ringbuffer::MpmcRingBuffer;
 use zenrc_shm::shm::MemoryHandle;
+use zenrc_shm::sync::Timeout;
 
 fn main() {
     let name = "/my_shared_mem";
     // let size: usize = 4096; // 4KB
 
     let mut mem_handle = MemoryHandle::open(name).expect("MemoryHandle::new failed");
-    let ring_buffer = MpmcRingBuffer::<i32>::try_into(mem_handle.get_mut_ptr().as_ptr()).unwrap();
-    loop {
-        let value = ring_buffer.read().unwrap();
-        println!("Read value from shared memory: {}", value);
-        std::thread::sleep(std::time::Duration::from_millis(100));
+    let mut ring_buffer =
+        MpmcRingBuffer::<i32>::try_into(mem_handle.get_mut_ptr().as_ptr()).unwrap();
+    // 登记为读者，发布者使用 try_write 时不会覆盖尚未读取的数据
+    ring_buffer.register_reader().unwrap();
+    // 发布者退出后停止读取并释放句柄
+    while !mem_handle.is_closing() {
+        // 阻塞等待发布者写入新值，无需轮询
+        match ring_buffer.read_blocking(Timeout::Val(Duration::from_secs(1))) {
+            Ok(value) => println!("Read value from shared memory: {}", value),
+            Err(err) => eprintln!("No new value: {}", err),
+        }
     }
+    println!("Publisher closed the segment, exiting");
 }
@@ -1,5 +1,4 @@
 use std::io::Cursor;
-use std::slice;
 use std::sync::Arc;
 
 use arrow::array::{Float32Array, ListArray, RecordBatch, StringArray, UInt32Array, UInt64Array};
@@ -99,10 +98,7 @@ fn main() -> anyhow::Result<()> {
         // ---------------------------
         // 拷贝到共享内存
         // ---------------------------
-        unsafe {
-            let slice = slice::from_raw_parts_mut(mem_handle.get_mut_ptr().as_ptr(), data.len());
-            slice.copy_from_slice(&data);
-        }
+        mem_handle.write_at(0, &data)?;
 
         println!("✅ Arrow RecordBatch (LaserScan) 已写入共享内存");
         println!("前64字节: {:02X?}", &data[..64.min(data.len())]);
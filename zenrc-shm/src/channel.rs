@@ -0,0 +1,462 @@
+//! 通过共享内存传递 Arrow `RecordBatch` 的单写多读通道
+//!
+//! 共享内存段开头是固定头部，随后是创建时写入一次的 schema（只含 schema 消息的 Arrow IPC 流），
+//! 再之后是数据区，每次 [`publish`](ShmArrowChannel::publish) 用一个 IPC 流覆盖其中的内容。
+//! 读者只需知道段名即可从头部恢复 schema 并解码最新的 batch。
+use std::cell::Cell;
+use std::io::Cursor;
+use std::mem::{ManuallyDrop, size_of};
+use std::sync::atomic::{AtomicU32, AtomicU64, AtomicUsize, Ordering, fence};
+use std::time::{Duration, Instant};
+
+use arrow::datatypes::SchemaRef;
+use arrow::ipc::reader::StreamReader;
+use arrow::ipc::writer::StreamWriter;
+use arrow::record_batch::RecordBatch;
+use nix::libc::{pthread_cond_t, pthread_mutex_t};
+
+use crate::errors::ChannelError;
+use crate::layout::ShmLayout;
+use crate::shm::MemoryHandle;
+use crate::sync::{SharedCondVar, SharedMutex, Timeout};
+
+// 共享内存头部的魔数（"ZRCA"）与布局版本，布局变化时递增 VERSION
+const MAGIC: u32 = 0x5A52_4341;
+const VERSION: u32 = 2;
+// schema 区与数据区的对齐
+const REGION_ALIGN: usize = 8;
+// 同一次写入停留在写入中超过该时长时，认为写者已崩溃
+const WRITER_STALL: Duration = Duration::from_millis(500);
+
+// 头部各字段相对起点的偏移，创建者与挂载方共用
+struct HeaderLayout {
+    magic: usize,
+    version: usize,
+    schema_len: usize,
+    // 顺序锁：奇数表示正在写入数据区，每次发布加 2
+    seq: usize,
+    body_len: usize,
+    // 保护已发布序号的互斥锁（锁之后紧跟 u64 序号）与发布通知用的条件变量
+    notify_lock: usize,
+    notify_cond: usize,
+    // schema 区的起点
+    schema: usize,
+}
+
+impl HeaderLayout {
+    fn new() -> Self {
+        let mut layout = ShmLayout::new();
+        let magic = layout.reserve::<AtomicU32>();
+        let version = layout.reserve::<u32>();
+        let schema_len = layout.reserve::<usize>();
+        let seq = layout.reserve::<AtomicU64>();
+        let body_len = layout.reserve::<AtomicUsize>();
+        let notify_lock = layout.reserve_bytes(
+            size_of::<pthread_mutex_t>() + size_of::<u64>(),
+            align_of::<pthread_mutex_t>(),
+        );
+        let notify_cond = layout.reserve::<pthread_cond_t>();
+        Self {
+            magic,
+            version,
+            schema_len,
+            seq,
+            body_len,
+            notify_lock,
+            notify_cond,
+            schema: layout.size().next_multiple_of(REGION_ALIGN),
+        }
+    }
+}
+
+/// 共享内存中的 Arrow 通道
+///
+/// 只能有一个写入者（创建者）；读者可以有多个，每次读取得到最近一次发布的 batch。
+/// 写入者与读者之间通过顺序锁同步：读取期间发生写入时读者自动重试，不会读到撕裂的数据。
+/// 发布后还会唤醒阻塞在 [`wait_for_next`](ShmArrowChannel::wait_for_next) 上的读者。
+pub struct ShmArrowChannel {
+    mem: MemoryHandle,
+    schema: SchemaRef,
+    // 数据区相对映射起点的偏移
+    body: usize,
+    // 已发布序号及其通知；只由创建者销毁，挂载方析构时不能影响其他进程仍在使用的锁
+    notify_lock: ManuallyDrop<SharedMutex<u64>>,
+    notify_cond: ManuallyDrop<SharedCondVar>,
+    owner: bool,
+    // 本实例最近读取到的发布序号
+    last_seen: Cell<u64>,
+}
+
+impl Drop for ShmArrowChannel {
+    fn drop(&mut self) {
+        if self.owner {
+            unsafe {
+                ManuallyDrop::drop(&mut self.notify_lock);
+                ManuallyDrop::drop(&mut self.notify_cond);
+            }
+        }
+    }
+}
+
+impl ShmArrowChannel {
+    /// 创建大小为 size 的共享内存段，并把 schema 写入头部
+    ///
+    /// 同名的共享内存段已存在时返回 [`AlreadyExists`](std::io::ErrorKind::AlreadyExists)
+    /// 错误，不会改动已有的段（可能是另一个发布者正在使用的通道）。
+    pub fn create(name: &str, size: usize, schema: SchemaRef) -> Result<Self, ChannelError> {
+        let mut mem = MemoryHandle::new(name, size)?;
+        // MemoryHandle::new 遇到已存在的段时会以非 owner 身份挂载
+        if !mem.is_owner() {
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::AlreadyExists,
+                format!("shared memory segment {} already exists", name),
+            )
+            .into());
+        }
+        let header = HeaderLayout::new();
+
+        let mut encoded = Vec::new();
+        StreamWriter::try_new(&mut encoded, &schema)?.finish()?;
+        let body = (header.schema + encoded.len()).next_multiple_of(REGION_ALIGN);
+        if body > size {
+            return Err(ChannelError::TooLarge {
+                needed: body,
+                available: size,
+            });
+        }
+        mem.write_at(header.schema, &encoded)?;
+
+        let base = mem.get_mut_ptr().as_ptr();
+        let (notify_lock, notify_cond) = unsafe {
+            std::ptr::write(base.add(header.version) as *mut u32, VERSION);
+            std::ptr::write(base.add(header.schema_len) as *mut usize, encoded.len());
+            std::ptr::write(base.add(header.seq) as *mut AtomicU64, AtomicU64::new(0));
+            std::ptr::write(base.add(header.body_len) as *mut AtomicUsize, AtomicUsize::new(0));
+            let (notify_lock, _) = SharedMutex::new(base.add(header.notify_lock), 0u64)?;
+            let (notify_cond, _) = SharedCondVar::new(base.add(header.notify_cond))?;
+            // 全部初始化完成后才写入魔数
+            (*(base.add(header.magic) as *const AtomicU32)).store(MAGIC, Ordering::Release);
+            (notify_lock, notify_cond)
+        };
+        Ok(Self {
+            mem,
+            schema,
+            body,
+            notify_lock: ManuallyDrop::new(notify_lock),
+            notify_cond: ManuallyDrop::new(notify_cond),
+            owner: true,
+            last_seen: Cell::new(0),
+        })
+    }
+
+    /// 按名称挂载已创建的通道，从头部读取 schema
+    ///
+    /// 魔数或版本不符（未初始化或布局不兼容）时返回
+    /// [`VersionMismatch`](ChannelError::VersionMismatch)。
+    pub fn open(name: &str) -> Result<Self, ChannelError> {
+        let mut mem = MemoryHandle::open(name)?;
+        let header = HeaderLayout::new();
+        let bytes = mem.as_slice();
+        if bytes.len() < header.schema {
+            return Err(ChannelError::VersionMismatch {
+                magic: 0,
+                version: 0,
+            });
+        }
+
+        let base = bytes.as_ptr();
+        let (magic, version, schema_len) = unsafe {
+            (
+                (*(base.add(header.magic) as *const AtomicU32)).load(Ordering::Acquire),
+                *(base.add(header.version) as *const u32),
+                *(base.add(header.schema_len) as *const usize),
+            )
+        };
+        if magic != MAGIC || version != VERSION {
+            return Err(ChannelError::VersionMismatch { magic, version });
+        }
+
+        let encoded = bytes
+            .get(header.schema..header.schema + schema_len)
+            .ok_or(ChannelError::VersionMismatch { magic, version })?;
+        let schema = StreamReader::try_new(Cursor::new(encoded), None)?.schema();
+        let body = (header.schema + schema_len).next_multiple_of(REGION_ALIGN);
+
+        let base = mem.get_mut_ptr().as_ptr();
+        let (notify_lock, notify_cond) = unsafe {
+            let (notify_lock, _) = SharedMutex::<u64>::try_into(base.add(header.notify_lock));
+            let (notify_cond, _) = SharedCondVar::try_into(base.add(header.notify_cond));
+            (notify_lock, notify_cond)
+        };
+        Ok(Self {
+            mem,
+            schema,
+            body,
+            notify_lock: ManuallyDrop::new(notify_lock),
+            notify_cond: ManuallyDrop::new(notify_cond),
+            owner: false,
+            last_seen: Cell::new(0),
+        })
+    }
+
+    /// 通道的 schema
+    pub fn schema(&self) -> &SchemaRef {
+        &self.schema
+    }
+
+    /// 数据区可容纳的最大字节数
+    pub fn capacity(&self) -> usize {
+        self.mem.as_slice().len() - self.body
+    }
+
+    /// 发布一个 batch，覆盖上一次发布的内容，返回该 batch 的序号（从 1 开始）
+    ///
+    /// batch 的 schema 必须与通道一致，否则返回 [`SchemaMismatch`](ChannelError::SchemaMismatch)；
+    /// 编码后超过数据区容量时返回 [`TooLarge`](ChannelError::TooLarge)，通道内容保持不变。
+    pub fn publish(&mut self, batch: &RecordBatch) -> Result<u64, ChannelError> {
+        if batch.schema() != self.schema {
+            return Err(ChannelError::SchemaMismatch);
+        }
+        let mut encoded = Vec::new();
+        {
+            let mut writer = StreamWriter::try_new(&mut encoded, &self.schema)?;
+            writer.write(batch)?;
+            writer.finish()?;
+        }
+        if encoded.len() > self.capacity() {
+            return Err(ChannelError::TooLarge {
+                needed: encoded.len(),
+                available: self.capacity(),
+            });
+        }
+
+        let header = HeaderLayout::new();
+        let base = self.mem.get_mut_ptr().as_ptr();
+        let (seq, body_len) = unsafe {
+            (
+                &*(base.add(header.seq) as *const AtomicU64),
+                &*(base.add(header.body_len) as *const AtomicUsize),
+            )
+        };
+        let start = seq.load(Ordering::Relaxed);
+        seq.store(start + 1, Ordering::Relaxed);
+        fence(Ordering::Release);
+        self.mem.write_at(self.body, &encoded)?;
+        body_len.store(encoded.len(), Ordering::Relaxed);
+        seq.store(start + 2, Ordering::Release);
+
+        let published = (start + 2) / 2;
+        // 持有锁更新序号再唤醒，保证读者检查序号与进入等待之间不会漏掉这次通知
+        let mut guard = self.notify_lock.lock()?;
+        *guard = published;
+        self.notify_cond.notify_all()?;
+        drop(guard);
+        Ok(published)
+    }
+
+    /// 解码最近一次发布的 batch，尚未发布时返回 [`Empty`](ChannelError::Empty)
+    ///
+    /// 数据区中流的 schema 与头部不一致时返回 [`SchemaMismatch`](ChannelError::SchemaMismatch)；
+    /// 写者在发布中途停止（如进程崩溃）超过 500ms 时返回
+    /// [`WriterStalled`](ChannelError::WriterStalled)，而不是一直等待。
+    pub fn latest(&self) -> Result<RecordBatch, ChannelError> {
+        let (seq, encoded) = self.snapshot()?.ok_or(ChannelError::Empty)?;
+        let batch = self.decode(&encoded)?;
+        self.last_seen.set(seq);
+        Ok(batch)
+    }
+
+    /// 阻塞直到有比本实例上次读取更新的 batch 发布，返回最新的 batch
+    ///
+    /// 本实例尚未读取过且通道中已有数据时立即返回；连续发布多个 batch 时只返回最新的一个。
+    /// 超时返回 [`Timeout`](ChannelError::Timeout)。
+    pub fn wait_for_next(&self, timeout: Timeout) -> Result<RecordBatch, ChannelError> {
+        let deadline = match timeout {
+            Timeout::Infinite => None,
+            Timeout::Val(dur) => Some(Instant::now() + dur),
+        };
+        let mut guard = self.notify_lock.lock()?;
+        while *guard <= self.last_seen.get() {
+            let timeout = match deadline {
+                None => Timeout::Infinite,
+                Some(deadline) => match deadline.checked_duration_since(Instant::now()) {
+                    Some(remaining) => Timeout::Val(remaining),
+                    None => return Err(ChannelError::Timeout),
+                },
+            };
+            guard = self.notify_cond.wait_timeout(guard, timeout)?.0;
+        }
+        drop(guard);
+        self.latest()
+    }
+
+    // 在两次写入之间拷贝数据区，返回发布序号和编码后的字节；尚未发布时返回 None
+    //
+    // 同一次写入持续超过 WRITER_STALL 时返回 WriterStalled
+    fn snapshot(&self) -> Result<Option<(u64, Vec<u8>)>, ChannelError> {
+        let header = HeaderLayout::new();
+        let base = self.mem.as_slice().as_ptr();
+        let (seq, body_len) = unsafe {
+            (
+                &*(base.add(header.seq) as *const AtomicU64),
+                &*(base.add(header.body_len) as *const AtomicUsize),
+            )
+        };
+        // 正在等待的写入序号及开始等待的时间
+        let mut waiting: Option<(u64, Instant)> = None;
+        loop {
+            let before = seq.load(Ordering::Acquire);
+            if before == 0 {
+                return Ok(None);
+            }
+            if before % 2 == 1 {
+                match waiting {
+                    Some((odd, since)) if odd == before => {
+                        if since.elapsed() >= WRITER_STALL {
+                            return Err(ChannelError::WriterStalled);
+                        }
+                    }
+                    _ => waiting = Some((before, Instant::now())),
+                }
+                std::thread::yield_now();
+                continue;
+            }
+            let len = body_len.load(Ordering::Relaxed).min(self.capacity());
+            let mut encoded = vec![0u8; len];
+            unsafe {
+                std::ptr::copy_nonoverlapping(base.add(self.body), encoded.as_mut_ptr(), len);
+            }
+            fence(Ordering::Acquire);
+            if seq.load(Ordering::Relaxed) == before {
+                return Ok(Some((before / 2, encoded)));
+            }
+        }
+    }
+
+    fn decode(&self, encoded: &[u8]) -> Result<RecordBatch, ChannelError> {
+        let mut reader = StreamReader::try_new(Cursor::new(encoded), None)?;
+        if reader.schema() != self.schema {
+            return Err(ChannelError::SchemaMismatch);
+        }
+        reader.next().ok_or(ChannelError::Empty)?.map_err(ChannelError::from)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+    use std::time::Duration;
+
+    use arrow::array::{Float32Array, UInt32Array};
+    use arrow::datatypes::{DataType, Field, Schema};
+
+    use super::*;
+
+    fn scan_schema() -> SchemaRef {
+        Arc::new(Schema::new(vec![
+            Field::new("seq", DataType::UInt32, false),
+            Field::new("range", DataType::Float32, false),
+        ]))
+    }
+
+    fn scan(schema: &SchemaRef, seq: u32) -> RecordBatch {
+        RecordBatch::try_new(
+            schema.clone(),
+            vec![
+                Arc::new(UInt32Array::from(vec![seq, seq + 1])),
+                Arc::new(Float32Array::from(vec![1.5, 2.5])),
+            ],
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn reader_recovers_schema_from_segment_name() {
+        let name = format!("/zenrc_test_arrow_{}", std::process::id());
+        let schema = scan_schema();
+        let mut writer = ShmArrowChannel::create(&name, 64 * 1024, schema.clone()).unwrap();
+
+        let reader = ShmArrowChannel::open(&name).unwrap();
+        assert_eq!(reader.schema(), &schema);
+        assert!(matches!(reader.latest(), Err(ChannelError::Empty)));
+
+        assert_eq!(writer.publish(&scan(&schema, 7)).unwrap(), 1);
+        assert_eq!(writer.publish(&scan(&schema, 9)).unwrap(), 2);
+        assert_eq!(reader.latest().unwrap(), scan(&schema, 9));
+    }
+
+    #[test]
+    fn create_refuses_existing_segment() {
+        let name = format!("/zenrc_test_arrow_exists_{}", std::process::id());
+        let schema = scan_schema();
+        let mut writer = ShmArrowChannel::create(&name, 64 * 1024, schema.clone()).unwrap();
+        writer.publish(&scan(&schema, 3)).unwrap();
+
+        let other = Arc::new(Schema::new(vec![Field::new("seq", DataType::UInt32, false)]));
+        let err = ShmArrowChannel::create(&name, 64 * 1024, other).err().unwrap();
+        let ChannelError::Io(err) = err else {
+            panic!("unexpected error: {}", err);
+        };
+        assert_eq!(err.kind(), std::io::ErrorKind::AlreadyExists);
+
+        // 已有的通道不受影响
+        let reader = ShmArrowChannel::open(&name).unwrap();
+        assert_eq!(reader.schema(), &schema);
+        assert_eq!(reader.latest().unwrap(), scan(&schema, 3));
+    }
+
+    #[test]
+    fn crashed_writer_is_reported_instead_of_spinning() {
+        let name = format!("/zenrc_test_arrow_stall_{}", std::process::id());
+        let schema = scan_schema();
+        let mut writer = ShmArrowChannel::create(&name, 64 * 1024, schema.clone()).unwrap();
+        writer.publish(&scan(&schema, 3)).unwrap();
+
+        // 模拟写者在发布中途崩溃：序号停留在奇数
+        let header = HeaderLayout::new();
+        let seq = unsafe { &*(writer.mem.as_slice().as_ptr().add(header.seq) as *const AtomicU64) };
+        seq.fetch_add(1, Ordering::Release);
+
+        let reader = ShmArrowChannel::open(&name).unwrap();
+        let started = Instant::now();
+        assert!(matches!(reader.latest(), Err(ChannelError::WriterStalled)));
+        assert!(started.elapsed() >= WRITER_STALL);
+        let timeout = Timeout::Val(Duration::from_secs(5));
+        assert!(matches!(reader.wait_for_next(timeout), Err(ChannelError::WriterStalled)));
+    }
+
+    #[test]
+    fn publish_rejects_foreign_schema() {
+        let name = format!("/zenrc_test_arrow_schema_{}", std::process::id());
+        let mut writer = ShmArrowChannel::create(&name, 64 * 1024, scan_schema()).unwrap();
+
+        let other = Arc::new(Schema::new(vec![Field::new("seq", DataType::UInt32, false)]));
+        let batch =
+            RecordBatch::try_new(other, vec![Arc::new(UInt32Array::from(vec![1]))]).unwrap();
+        assert!(matches!(writer.publish(&batch), Err(ChannelError::SchemaMismatch)));
+    }
+
+    #[test]
+    fn wait_for_next_blocks_until_publish() {
+        let name = format!("/zenrc_test_arrow_wait_{}", std::process::id());
+        let schema = scan_schema();
+        let mut writer = ShmArrowChannel::create(&name, 64 * 1024, schema.clone()).unwrap();
+
+        let reader_name = name.clone();
+        let handle = std::thread::spawn(move || {
+            let reader = ShmArrowChannel::open(&reader_name).unwrap();
+            let started = Instant::now();
+            let batch = reader.wait_for_next(Timeout::Val(Duration::from_secs(5))).unwrap();
+            // 已读取过最新的 batch，再次等待会超时
+            let again = reader.wait_for_next(Timeout::Val(Duration::from_millis(20)));
+            (batch, started.elapsed(), matches!(again, Err(ChannelError::Timeout)))
+        });
+
+        std::thread::sleep(Duration::from_millis(50));
+        writer.publish(&scan(&schema, 3)).unwrap();
+        let (batch, waited, timed_out) = handle.join().unwrap();
+        assert_eq!(batch, scan(&schema, 3));
+        assert!(waited >= Duration::from_millis(40));
+        assert!(timed_out);
+    }
+}
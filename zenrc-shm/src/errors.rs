@@ -14,6 +14,16 @@ pub enum MutexLockError {
     TimeoutError(i32),
 }
 
+#[derive(Debug, Error)]
+pub enum CondVarError {
+    #[error("CondVar failed init with code {0}")]
+    InitError(i32),
+    #[error("Failed to wait on CondVar with code {0}")]
+    WaitError(i32),
+    #[error("Failed to notify CondVar with code {0}")]
+    NotifyError(i32),
+}
+
 #[derive(Debug, Error)]
 pub enum RwLockError {
     #[error("RwLock failed init with code {0}")]
@@ -30,8 +40,44 @@ pub enum RwLockError {
     ReadUnlockError(i32),
     #[error("Failed to unlock Write RwLock with code {0}")]
     WriteUnlockError(i32),
+    #[error("Timeout while trying to lock RwLock with code {0}")]
+    Timeout(i32),
     #[error("Try into SharedRwLock failed due to invalid pointer")]
     IntoError,
 	#[error("RwLock is empty, no data to read")]
 	Empty,
+    #[error("Incompatible or uninitialized shared memory (magic {magic:#x}, version {version})")]
+    VersionMismatch { magic: u32, version: u32 },
+    #[error("No free reader cursor left in ring buffer")]
+    TooManyReaders,
+    #[error("Only the creator of the ring buffer may reset it")]
+    NotOwner,
+}
+
+#[derive(Debug, Error)]
+#[error("Ring buffer is full, the slowest reader has not caught up")]
+pub struct RingFull;
+
+#[derive(Debug, Error)]
+pub enum ChannelError {
+    #[error("Shared memory error: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("Arrow IPC error: {0}")]
+    Arrow(#[from] arrow::error::ArrowError),
+    #[error("Incompatible or uninitialized Arrow channel (magic {magic:#x}, version {version})")]
+    VersionMismatch { magic: u32, version: u32 },
+    #[error("Batch schema does not match the channel schema")]
+    SchemaMismatch,
+    #[error("Encoded data needs {needed} bytes but only {available} are available")]
+    TooLarge { needed: usize, available: usize },
+    #[error("No batch has been published yet")]
+    Empty,
+    #[error("Timed out waiting for a new batch")]
+    Timeout,
+    #[error("The writer stalled in the middle of publishing a batch")]
+    WriterStalled,
+    #[error(transparent)]
+    Mutex(#[from] MutexLockError),
+    #[error(transparent)]
+    CondVar(#[from] CondVarError),
 }
@@ -0,0 +1,101 @@
+//! 共享内存布局：按声明顺序为字段分配满足对齐要求的偏移
+//!
+//! 写入方和读取方使用同一段布局代码计算偏移，避免两边各自手写 `align_offset` 导致不一致。
+use std::mem::{align_of, size_of};
+
+/// 可以直接存放在共享内存中的类型
+///
+/// 共享内存会被其他进程映射到不同地址，值中不能含有指针、引用或堆分配
+/// （`String`、`Vec`、`Box` 等），否则其他进程会读到本进程的地址。
+/// 要求 `Copy` 可在编译期排除大部分此类类型：
+///
+/// ```compile_fail
+/// use zenrc_shm::ringbuffer::MpmcRingBuffer;
+/// use zenrc_shm::shm::MemoryHandle;
+///
+/// let mut handle = MemoryHandle::anonymous(4096).unwrap();
+/// let ring = MpmcRingBuffer::<String>::new(&mut handle, 4);
+/// ```
+///
+/// # Safety
+///
+/// 实现者必须保证类型是纯数据：不含任何指针或引用，任意进程按位读取都有意义。
+/// 自定义结构体通常还应使用 `#[repr(C)]`，使不同编译单元的布局一致。
+pub unsafe trait ShmSafe: Copy + 'static {}
+
+macro_rules! impl_shm_safe {
+    ($($ty:ty),* $(,)?) => {
+        $(unsafe impl ShmSafe for $ty {})*
+    };
+}
+
+impl_shm_safe!(u8, u16, u32, u64, u128, usize, i8, i16, i32, i64, i128, isize);
+impl_shm_safe!(f32, f64, bool, char, ());
+
+unsafe impl<T: ShmSafe, const N: usize> ShmSafe for [T; N] {}
+
+/// 共享内存布局构建器
+///
+/// 偏移相对于布局起点，起点本身需要按 [`align`](ShmLayout::align) 对齐。
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ShmLayout {
+    size: usize,
+    align: usize,
+}
+
+impl ShmLayout {
+    pub const fn new() -> Self {
+        Self { size: 0, align: 1 }
+    }
+
+    /// 预留一个 T，返回其偏移
+    pub fn reserve<T>(&mut self) -> usize {
+        self.reserve_bytes(size_of::<T>(), align_of::<T>())
+    }
+
+    /// 预留连续 n 个 T，返回第一个元素的偏移
+    pub fn reserve_array<T>(&mut self, n: usize) -> usize {
+        self.reserve_bytes(size_of::<T>() * n, align_of::<T>())
+    }
+
+    /// 按给定大小和对齐预留一段字节，返回其偏移；align 必须是 2 的幂
+    pub fn reserve_bytes(&mut self, size: usize, align: usize) -> usize {
+        assert!(align.is_power_of_two(), "alignment must be a power of two");
+        let offset = self.size.next_multiple_of(align);
+        self.size = offset + size;
+        self.align = self.align.max(align);
+        offset
+    }
+
+    /// 已预留的总字节数
+    pub fn size(&self) -> usize {
+        self.size
+    }
+
+    /// 布局起点需要满足的对齐
+    pub fn align(&self) -> usize {
+        self.align
+    }
+}
+
+impl Default for ShmLayout {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reserve_pads_to_field_alignment() {
+        let mut layout = ShmLayout::new();
+        assert_eq!(layout.reserve::<u8>(), 0);
+        assert_eq!(layout.reserve::<u64>(), 8);
+        assert_eq!(layout.reserve::<u16>(), 16);
+        assert_eq!(layout.reserve_array::<u32>(3), 20);
+        assert_eq!(layout.size(), 32);
+        assert_eq!(layout.align(), 8);
+    }
+}
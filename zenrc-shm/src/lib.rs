@@ -1,4 +1,6 @@
+pub mod layout;
 pub mod shm;
 pub mod sync;
 pub mod errors;
 pub mod ringbuffer;
+pub mod channel;
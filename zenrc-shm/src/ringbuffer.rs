@@ -1,18 +1,119 @@
 use std::cell::Cell;
-use std::sync::atomic::AtomicUsize;
+use std::mem::{ManuallyDrop, size_of};
+use std::sync::atomic::{AtomicU32, AtomicUsize, Ordering};
+use std::time::Instant;
+
+use nix::libc::{ETIMEDOUT, pthread_cond_t, pthread_mutex_t, pthread_rwlock_t};
 
 use crate::errors;
+use crate::layout::{ShmLayout, ShmSafe};
 use crate::shm::MemoryHandle;
-use crate::sync::SharedRwLock;
+use crate::sync::{SharedCondVar, SharedMutex, SharedRwLock, Timeout};
+
+// 共享内存头部的魔数（"ZRCB"）与布局版本，布局变化时递增 VERSION
+const MAGIC: u32 = 0x5A52_4342;
+const VERSION: u32 = 4;
+
+/// 可同时登记游标的读者数量上限
+pub const MAX_READERS: usize = 16;
+// 空闲的读者游标
+const FREE_CURSOR: usize = usize::MAX;
+
+// 头部各字段相对起点的偏移，创建者与挂载方共用
+struct HeaderLayout {
+    align: usize,
+    magic: usize,
+    version: usize,
+    capacity: usize,
+    write_seq: usize,
+    // reset() 的次数，读者据此发现序号已被清零
+    epoch: usize,
+    // 写入通知用的互斥锁与条件变量
+    notify_lock: usize,
+    notify_cond: usize,
+    // MAX_READERS 个读者游标
+    readers: usize,
+    // 第一个槽位的起点
+    slots: usize,
+}
+
+impl HeaderLayout {
+    fn new() -> Self {
+        let mut layout = ShmLayout::new();
+        let magic = layout.reserve::<AtomicU32>();
+        let version = layout.reserve::<u32>();
+        let capacity = layout.reserve::<usize>();
+        let write_seq = layout.reserve::<AtomicUsize>();
+        let epoch = layout.reserve::<AtomicUsize>();
+        let notify_lock = layout.reserve::<pthread_mutex_t>();
+        let notify_cond = layout.reserve::<pthread_cond_t>();
+        let readers = layout.reserve_array::<AtomicUsize>(MAX_READERS);
+        Self {
+            align: layout.align(),
+            magic,
+            version,
+            capacity,
+            write_seq,
+            epoch,
+            notify_lock,
+            notify_cond,
+            readers,
+            slots: layout.size(),
+        }
+    }
+}
 
+/// 基于共享内存的多生产者多消费者环形缓冲区
+///
+/// 内存布局：magic、version、capacity、write_seq、epoch、通知用的互斥锁和条件变量、
+/// [`MAX_READERS`] 个读者游标，随后是 capacity 个 SharedRwLock<T> 槽位。
+/// 创建者初始化全部内容后最后写入 magic，挂载方据此校验共享内存已由兼容的写入者初始化。
+/// T 直接存放在共享内存中，因此必须实现 [`ShmSafe`]。
+///
+/// 需要背压的读者调用 [`register_reader`](MpmcRingBuffer::register_reader) 登记，
+/// 在共享内存中发布自己的读取进度；[`try_write`](MpmcRingBuffer::try_write)
+/// 据此在写入会覆盖最慢读者尚未读取的数据时拒绝写入，[`write`](MpmcRingBuffer::write) 则总是覆盖。
+/// 只写入或不关心丢数据的挂载方不必登记，也不占用读者名额。
+///
+/// 登记的读者进程崩溃时游标不会被释放，会一直限制 `try_write`（积压达到容量后持续返回
+/// [`RingFull`](errors::RingFull)）；此时只能改用 `write` 覆盖写入，或由创建者重新初始化缓冲区。
 pub struct MpmcRingBuffer<T> {
     buffer: Vec<SharedRwLock<T>>,
     capacity: *mut usize,
     write_seq: *mut AtomicUsize,
     read_seq: Cell<usize>,
+    epoch: *mut AtomicUsize,
+    // 本实例上次读取时看到的 epoch
+    seen_epoch: Cell<usize>,
+    readers: *mut AtomicUsize,
+    // 本实例登记的游标下标，未调用 register_reader 时为 None
+    cursor: Option<usize>,
+    // 只由创建者销毁，挂载方析构时不能影响其他进程仍在使用的锁
+    notify_lock: ManuallyDrop<SharedMutex<()>>,
+    notify_cond: ManuallyDrop<SharedCondVar>,
+    owner: bool,
 }
 
-impl<T: Default> MpmcRingBuffer<T> {
+// SAFETY: 指针都指向共享内存中以 PTHREAD_PROCESS_SHARED 初始化的锁、条件变量和原子变量，
+// 移动到其他线程后仍然有效；本地的读取进度（Cell）随句柄一起移动。
+// 句柄包含 Cell，因此不实现 Sync，每个线程应各自挂载。
+unsafe impl<T: Send> Send for MpmcRingBuffer<T> {}
+
+impl<T> Drop for MpmcRingBuffer<T> {
+    fn drop(&mut self) {
+        if let Some(cursor) = self.cursor {
+            unsafe { (*self.readers.add(cursor)).store(FREE_CURSOR, Ordering::Release) };
+        }
+        if self.owner {
+            unsafe {
+                ManuallyDrop::drop(&mut self.notify_lock);
+                ManuallyDrop::drop(&mut self.notify_cond);
+            }
+        }
+    }
+}
+
+impl<T: ShmSafe + Default> MpmcRingBuffer<T> {
     pub fn new(
         mem_handle: &mut MemoryHandle,
         capacity: usize,
@@ -21,15 +122,26 @@ impl<T: Default> MpmcRingBuffer<T> {
             if !mem_handle.is_owner() {
                 return MpmcRingBuffer::<T>::try_into(mem_handle.get_mut_ptr().as_ptr());
             }
+            let header = HeaderLayout::new();
             let mem = mem_handle.get_mut_ptr().as_ptr();
-            let padding = mem.align_offset(std::mem::size_of::<*mut u8>() as _);
-            let cap_ptr = mem.add(padding) as *mut usize;
+            let base = mem.add(mem.align_offset(header.align));
+            let magic_ptr = base.add(header.magic) as *mut AtomicU32;
+            std::ptr::write(magic_ptr, AtomicU32::new(0));
+            std::ptr::write(base.add(header.version) as *mut u32, VERSION);
+            let cap_ptr = base.add(header.capacity) as *mut usize;
             std::ptr::write(cap_ptr, capacity);
-            let seq_ptr = mem.add(padding + std::mem::size_of::<usize>()) as *mut AtomicUsize;
+            let seq_ptr = base.add(header.write_seq) as *mut AtomicUsize;
             std::ptr::write(seq_ptr, AtomicUsize::new(0));
+            let epoch = base.add(header.epoch) as *mut AtomicUsize;
+            std::ptr::write(epoch, AtomicUsize::new(0));
+            let (notify_lock, _) = SharedMutex::new(base.add(header.notify_lock), ()).unwrap();
+            let (notify_cond, _) = SharedCondVar::new(base.add(header.notify_cond)).unwrap();
+            let readers = base.add(header.readers) as *mut AtomicUsize;
+            for i in 0..MAX_READERS {
+                std::ptr::write(readers.add(i), AtomicUsize::new(FREE_CURSOR));
+            }
             let mut buffer = Vec::with_capacity(capacity);
-            let mut ptr = mem
-                .add(std::mem::size_of::<usize>() + std::mem::size_of::<AtomicUsize>() + padding);
+            let mut ptr = base.add(header.slots);
             for _ in 0..capacity {
                 let slot_padding = ptr.align_offset(std::mem::size_of::<*mut u8>() as _);
                 let (slot, size) =
@@ -37,31 +149,98 @@ impl<T: Default> MpmcRingBuffer<T> {
                 buffer.push(slot);
                 ptr = ptr.add(size + slot_padding);
             }
+            // 全部初始化完成后才写入魔数
+            (*magic_ptr).store(MAGIC, Ordering::Release);
 
             Ok(Self {
                 buffer,
                 capacity: cap_ptr,
                 write_seq: seq_ptr,
                 read_seq: Cell::new(0),
+                epoch,
+                seen_epoch: Cell::new(0),
+                readers,
+                cursor: None,
+                notify_lock: ManuallyDrop::new(notify_lock),
+                notify_cond: ManuallyDrop::new(notify_cond),
+                owner: true,
             })
         }
     }
 
+    /// 容量为 capacity 的环形缓冲区占用的共享内存字节数
+    ///
+    /// 包括头部、读者游标以及每个槽位的读写锁、T 和对齐填充，可直接用作 [`MemoryHandle`] 的大小。
+    /// 假定共享内存起点满足头部的对齐要求（`mmap` 返回的地址按页对齐）。
+    pub fn required_bytes(capacity: usize) -> usize {
+        let slot = size_of::<pthread_rwlock_t>() + size_of::<T>();
+        // 与 new() 相同，每个槽位起点按指针大小对齐
+        (0..capacity).fold(HeaderLayout::new().slots, |end, _| {
+            end.next_multiple_of(size_of::<*mut u8>()) + slot
+        })
+    }
+
+    /// 写入一个值并唤醒阻塞在 [`read_blocking`](MpmcRingBuffer::read_blocking) 上的读者
     pub fn write(&self, value: T) {
         let write_seq =
             unsafe { (*self.write_seq).fetch_add(1, std::sync::atomic::Ordering::Release) };
+        self.store(write_seq, value);
+    }
+
+    /// 写入一个值，若会覆盖最慢的已登记读者尚未读取的数据则返回 [`RingFull`](errors::RingFull)
+    ///
+    /// 没有已登记读者时与 [`write`](MpmcRingBuffer::write) 相同。
+    pub fn try_write(&self, value: T) -> Result<(), errors::RingFull> {
+        let capacity = unsafe { *self.capacity };
+        let write_seq = unsafe { &*self.write_seq };
+        let mut seq = write_seq.load(Ordering::Acquire);
+        loop {
+            if let Some(slowest) = self.slowest_cursor()
+                && seq.saturating_sub(slowest) >= capacity
+            {
+                return Err(errors::RingFull);
+            }
+            match write_seq.compare_exchange_weak(seq, seq + 1, Ordering::AcqRel, Ordering::Acquire)
+            {
+                Ok(_) => break,
+                Err(current) => seq = current,
+            }
+        }
+        self.store(seq, value);
+        Ok(())
+    }
+
+    // 最慢的已登记读者已读取到的序号
+    fn slowest_cursor(&self) -> Option<usize> {
+        (0..MAX_READERS)
+            .map(|i| unsafe { (*self.readers.add(i)).load(Ordering::Acquire) })
+            .filter(|&cursor| cursor != FREE_CURSOR)
+            .min()
+    }
+
+    // 把 value 写入 write_seq 对应的槽位并唤醒读者
+    fn store(&self, write_seq: usize, value: T) {
         let index = write_seq % unsafe { *self.capacity };
         println!("Writing at write_seq: {}", write_seq);
         let mut guard = self.buffer[index].write().unwrap();
         *guard = value;
+        drop(guard);
+        // 持有通知锁再唤醒，保证读者检查条件与进入等待之间不会漏掉这次通知
+        let _notify = self.notify_lock.lock().unwrap();
+        self.notify_cond.notify_all().unwrap();
     }
 
-    pub fn read(&self) -> Result<T, errors::RwLockError>
-    where
-        T: Copy,
-    {
+    /// 读取下一个值：首次读取得到最新的值，之后按顺序读取，没有新值时重复返回最近读取的值
+    ///
+    /// 从未写入（或 [`reset`](MpmcRingBuffer::reset) 后尚未写入）时返回
+    /// [`Empty`](errors::RwLockError::Empty)。
+    pub fn read(&self) -> Result<T, errors::RwLockError> {
+        self.sync_epoch();
         println!("Current read_seq: {}", self.read_seq.get());
         let seq = unsafe { (*self.write_seq).load(std::sync::atomic::Ordering::Acquire) };
+        if seq == 0 {
+            return Err(errors::RwLockError::Empty);
+        }
         if self.read_seq.get() == 0 {
             self.read_seq.set(seq);
         } else if self.read_seq.get() < seq {
@@ -69,22 +248,128 @@ impl<T: Default> MpmcRingBuffer<T> {
         } else {
             // return Err(errors::RwLockError::Empty);
         }
+        if let Some(cursor) = self.cursor {
+            let read_seq = self.read_seq.get();
+            unsafe { (*self.readers.add(cursor)).store(read_seq, Ordering::Release) };
+        }
         let index = (self.read_seq.get() - 1) % unsafe { *self.capacity };
         let guard = self.buffer[index].read().unwrap();
         Ok(*guard)
     }
 
+    /// 阻塞直到有新数据可读或超时，超时返回 [`Timeout`](errors::RwLockError::Timeout)
+    ///
+    /// 与 [`read`](MpmcRingBuffer::read) 相同，首次读取得到最新的值，之后按顺序读取。
+    pub fn read_blocking(&self, timeout: Timeout) -> Result<T, errors::RwLockError> {
+        let deadline = match timeout {
+            Timeout::Infinite => None,
+            Timeout::Val(dur) => Some(Instant::now() + dur),
+        };
+        let mut guard = self.notify_lock.lock().unwrap();
+        while !self.has_unread() {
+            let timeout = match deadline {
+                None => Timeout::Infinite,
+                Some(deadline) => match deadline.checked_duration_since(Instant::now()) {
+                    Some(remaining) => Timeout::Val(remaining),
+                    None => return Err(errors::RwLockError::Timeout(ETIMEDOUT)),
+                },
+            };
+            guard = self.notify_cond.wait_timeout(guard, timeout).unwrap().0;
+        }
+        drop(guard);
+        self.read()
+    }
+
+    // 是否有尚未读取的写入
+    fn has_unread(&self) -> bool {
+        self.sync_epoch();
+        let seq = unsafe { (*self.write_seq).load(Ordering::Acquire) };
+        self.read_seq.get() < seq
+    }
+
+    // 创建者 reset() 后丢弃本地的读取进度，从新的数据流开头读取
+    fn sync_epoch(&self) {
+        let epoch = unsafe { (*self.epoch).load(Ordering::Acquire) };
+        if epoch != self.seen_epoch.get() {
+            self.seen_epoch.set(epoch);
+            self.read_seq.set(0);
+        }
+    }
+
+    /// 累计写入次数，[`reset`](MpmcRingBuffer::reset) 后从零开始
+    pub fn write_count(&self) -> usize {
+        unsafe { (*self.write_seq).load(Ordering::Acquire) }
+    }
+
+    /// 把写入序号和所有已登记读者的游标清零，用于复用同一段共享内存重新开始一个数据流
+    ///
+    /// 只有创建者可以重置，挂载方调用返回 [`NotOwner`](errors::RwLockError::NotOwner)。
+    /// 槽位中的旧数据不会被清除；读者在下一次读取时发现重置并从新数据流开头读取。
+    /// 调用方需保证重置期间没有并发写入，否则该写入可能落在旧数据流中。
+    pub fn reset(&self) -> Result<(), errors::RwLockError> {
+        if !self.owner {
+            return Err(errors::RwLockError::NotOwner);
+        }
+        let _notify = self.notify_lock.lock().unwrap();
+        unsafe {
+            (*self.write_seq).store(0, Ordering::Release);
+            for i in 0..MAX_READERS {
+                let cursor = &*self.readers.add(i);
+                let _ = cursor.fetch_update(Ordering::AcqRel, Ordering::Acquire, |seq| {
+                    (seq != FREE_CURSOR).then_some(0)
+                });
+            }
+            (*self.epoch).fetch_add(1, Ordering::AcqRel);
+        }
+        self.sync_epoch();
+        Ok(())
+    }
+
+    /// 登记为读者，此后 [`try_write`](MpmcRingBuffer::try_write) 不会覆盖本实例尚未读取的数据
+    ///
+    /// 从当前写入位置开始登记，之前的数据不计入积压；已登记时不做任何事。
+    /// 登记在本实例析构时释放。已有 [`MAX_READERS`] 个读者时返回
+    /// [`TooManyReaders`](errors::RwLockError::TooManyReaders)。
+    pub fn register_reader(&mut self) -> Result<(), errors::RwLockError> {
+        if self.cursor.is_some() {
+            return Ok(());
+        }
+        let seq = unsafe { (*self.write_seq).load(Ordering::Acquire) };
+        let cursor = (0..MAX_READERS).find(|&i| unsafe {
+            (*self.readers.add(i))
+                .compare_exchange(FREE_CURSOR, seq, Ordering::AcqRel, Ordering::Relaxed)
+                .is_ok()
+        });
+        self.cursor = Some(cursor.ok_or(errors::RwLockError::TooManyReaders)?);
+        Ok(())
+    }
+
+    /// 挂载已由创建者初始化的环形缓冲区
+    ///
+    /// 挂载方不会自动登记为读者，需要背压时调用 [`register_reader`](MpmcRingBuffer::register_reader)。
+    /// 魔数或版本不符（未初始化、初始化尚未完成或布局不兼容）时返回
+    /// [`VersionMismatch`](errors::RwLockError::VersionMismatch)。
     pub fn try_into(mem: *mut u8) -> Result<Self, errors::RwLockError> {
         unsafe {
-            let padding = mem.align_offset(std::mem::size_of::<*mut u8>() as _);
-            let cap_ptr = mem.add(padding) as *mut usize;
+            let header = HeaderLayout::new();
+            let base = mem.add(mem.align_offset(header.align));
+            let magic = (*(base.add(header.magic) as *const AtomicU32)).load(Ordering::Acquire);
+            let version = *(base.add(header.version) as *const u32);
+            if magic != MAGIC || version != VERSION {
+                return Err(errors::RwLockError::VersionMismatch { magic, version });
+            }
+            let cap_ptr = base.add(header.capacity) as *mut usize;
             let capacity = *cap_ptr;
-            let seq_ptr = mem.add(padding + std::mem::size_of::<usize>()) as *mut AtomicUsize;
+            let seq_ptr = base.add(header.write_seq) as *mut AtomicUsize;
+            let epoch = base.add(header.epoch) as *mut AtomicUsize;
+            let (notify_lock, _) = SharedMutex::<()>::try_into(base.add(header.notify_lock));
+            let (notify_cond, _) = SharedCondVar::try_into(base.add(header.notify_cond));
+            let readers = base.add(header.readers) as *mut AtomicUsize;
             let mut buffer = Vec::with_capacity(capacity);
-            let mut ptr = mem
-                .add(std::mem::size_of::<usize>() + std::mem::size_of::<AtomicUsize>() + padding);
+            let mut ptr = base.add(header.slots);
             for _ in 0..capacity {
                 let slot_padding: usize = ptr.align_offset(std::mem::size_of::<*mut u8>() as _);
+                // 挂载方的槽位锁析构时不销毁锁，锁只由创建者的槽位销毁
                 let (slot, size) = SharedRwLock::<T>::try_into(ptr.add(slot_padding)).unwrap();
                 buffer.push(slot);
                 ptr = ptr.add(size + slot_padding);
@@ -98,7 +383,191 @@ impl<T: Default> MpmcRingBuffer<T> {
                 capacity: cap_ptr,
                 write_seq: seq_ptr,
                 read_seq: Cell::new(0),
+                epoch,
+                seen_epoch: Cell::new((*epoch).load(Ordering::Acquire)),
+                readers,
+                cursor: None,
+                notify_lock: ManuallyDrop::new(notify_lock),
+                notify_cond: ManuallyDrop::new(notify_cond),
+                owner: false,
             })
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::*;
+
+    #[test]
+    fn header_layout_matches_hand_computed_offsets() {
+        let header = HeaderLayout::new();
+        assert_eq!(header.align, 8);
+        assert_eq!(header.magic, 0);
+        assert_eq!(header.version, 4);
+        assert_eq!(header.capacity, 8);
+        assert_eq!(header.write_seq, 16);
+        assert_eq!(header.epoch, 24);
+        assert_eq!(header.notify_lock, 32);
+        assert_eq!(header.notify_cond, 32 + size_of::<pthread_mutex_t>());
+        assert_eq!(header.readers, header.notify_cond + size_of::<pthread_cond_t>());
+        assert_eq!(header.slots, header.readers + MAX_READERS * size_of::<usize>());
+    }
+
+    #[test]
+    fn attach_rejects_uninitialized_segment() {
+        // 匿名段不是 owner，new() 会走挂载路径，而段内容全为 0
+        let mut handle = MemoryHandle::anonymous(4096).unwrap();
+        let err = MpmcRingBuffer::<i32>::new(&mut handle, 4).err().unwrap();
+        assert!(matches!(
+            err,
+            errors::RwLockError::VersionMismatch { magic: 0, version: 0 }
+        ));
+    }
+
+    #[test]
+    fn attach_accepts_initialized_segment() {
+        let mut creator = MemoryHandle::anonymous(4096).unwrap();
+        creator.set_owner(true);
+        let ring = MpmcRingBuffer::<i32>::new(&mut creator, 4).unwrap();
+        ring.write(42);
+
+        let mut reader = creator.try_clone().unwrap();
+        let attached = MpmcRingBuffer::<i32>::try_into(reader.get_mut_ptr().as_ptr()).unwrap();
+        assert_eq!(attached.read().unwrap(), 42);
+    }
+
+    #[test]
+    fn read_blocking_wakes_on_write() {
+        let mut creator = MemoryHandle::anonymous(4096).unwrap();
+        creator.set_owner(true);
+        let ring = MpmcRingBuffer::<i32>::new(&mut creator, 4).unwrap();
+
+        let mut reader = creator.try_clone().unwrap();
+        let addr = reader.get_mut_ptr().as_ptr() as usize;
+        let handle = std::thread::spawn(move || {
+            let attached = MpmcRingBuffer::<i32>::try_into(addr as *mut u8).unwrap();
+            let started = Instant::now();
+            let value = attached.read_blocking(Timeout::Val(Duration::from_secs(5)));
+            (value.unwrap(), started.elapsed())
+        });
+
+        std::thread::sleep(Duration::from_millis(50));
+        ring.write(7);
+        let (value, waited) = handle.join().unwrap();
+        assert_eq!(value, 7);
+        assert!(waited >= Duration::from_millis(40));
+        drop(reader);
+    }
+
+    #[test]
+    fn read_blocking_times_out_without_writer() {
+        let mut creator = MemoryHandle::anonymous(4096).unwrap();
+        creator.set_owner(true);
+        let ring = MpmcRingBuffer::<i32>::new(&mut creator, 4).unwrap();
+        ring.write(1);
+        assert_eq!(ring.read().unwrap(), 1);
+
+        let err = ring.read_blocking(Timeout::Val(Duration::from_millis(20)));
+        assert!(matches!(err, Err(errors::RwLockError::Timeout(ETIMEDOUT))));
+    }
+
+    #[test]
+    fn try_write_reports_full_before_reads() {
+        let mut creator = MemoryHandle::anonymous(4096).unwrap();
+        creator.set_owner(true);
+        let ring = MpmcRingBuffer::<i32>::new(&mut creator, 4).unwrap();
+
+        let mut reader = creator.try_clone().unwrap();
+        let mut attached =
+            MpmcRingBuffer::<i32>::try_into(reader.get_mut_ptr().as_ptr()).unwrap();
+        attached.register_reader().unwrap();
+        for i in 0..4 {
+            ring.try_write(i).unwrap();
+        }
+        assert!(matches!(ring.try_write(4), Err(errors::RingFull)));
+
+        // 读者读取后腾出空间
+        assert_eq!(attached.read().unwrap(), 3);
+        ring.try_write(4).unwrap();
+
+        // 读者注销后不再限制写入；挂载方析构时不销毁槽位的锁
+        assert!(attached.buffer.iter().all(|slot| !slot.is_owner()));
+        assert!(ring.buffer.iter().all(|slot| slot.is_owner()));
+        drop(attached);
+        for i in 5..10 {
+            ring.try_write(i).unwrap();
+        }
+    }
+
+    #[test]
+    fn unregistered_attachers_do_not_limit_writes() {
+        let mut creator = MemoryHandle::anonymous(4096).unwrap();
+        creator.set_owner(true);
+        let ring = MpmcRingBuffer::<i32>::new(&mut creator, 4).unwrap();
+
+        // 挂载数量不受读者名额限制，只写入或从不读取的挂载方也不会阻塞 try_write
+        let mem = creator.get_mut_ptr().as_ptr();
+        let mut attached: Vec<_> = (0..MAX_READERS + 1)
+            .map(|_| MpmcRingBuffer::<i32>::try_into(mem).unwrap())
+            .collect();
+        for i in 0..10 {
+            attached[0].try_write(i).unwrap();
+        }
+
+        for ring in &mut attached[..MAX_READERS] {
+            ring.register_reader().unwrap();
+        }
+        assert!(matches!(
+            attached[MAX_READERS].register_reader(),
+            Err(errors::RwLockError::TooManyReaders)
+        ));
+        assert!(attached.iter().flat_map(|ring| &ring.buffer).all(|slot| !slot.is_owner()));
+        drop(attached);
+        ring.try_write(10).unwrap();
+    }
+
+    #[test]
+    fn required_bytes_covers_exactly_the_initialized_region() {
+        let capacity = 5;
+        let required = MpmcRingBuffer::<u64>::required_bytes(capacity);
+        let mut creator = MemoryHandle::anonymous(required + 64).unwrap();
+        creator.set_owner(true);
+        creator.as_mut_slice().fill(0xAA);
+        let _ring = MpmcRingBuffer::<u64>::new(&mut creator, capacity).unwrap();
+
+        // 最后一个槽位的值为 T::default()，紧随其后的字节未被触及
+        let bytes = creator.as_slice();
+        assert_eq!(bytes[required - size_of::<u64>()..required], [0; 8]);
+        assert!(bytes[required..].iter().all(|&b| b == 0xAA));
+        assert_eq!(MpmcRingBuffer::<u64>::required_bytes(0), HeaderLayout::new().slots);
+    }
+
+    #[test]
+    fn reset_restarts_the_stream() {
+        let mut creator = MemoryHandle::anonymous(4096).unwrap();
+        creator.set_owner(true);
+        let ring = MpmcRingBuffer::<i32>::new(&mut creator, 4).unwrap();
+
+        let mut reader = creator.try_clone().unwrap();
+        let attached = MpmcRingBuffer::<i32>::try_into(reader.get_mut_ptr().as_ptr()).unwrap();
+        for i in 1..=3 {
+            ring.write(i);
+        }
+        assert_eq!(attached.read().unwrap(), 3);
+        assert_eq!(ring.write_count(), 3);
+
+        assert!(matches!(attached.reset(), Err(errors::RwLockError::NotOwner)));
+        ring.reset().unwrap();
+        assert_eq!(ring.write_count(), 0);
+        // 重置后尚未写入时没有可读的值
+        assert!(matches!(attached.read(), Err(errors::RwLockError::Empty)));
+        assert!(matches!(ring.read(), Err(errors::RwLockError::Empty)));
+
+        // 读者从新数据流的第一个值开始读取
+        ring.write(10);
+        assert_eq!(attached.read().unwrap(), 10);
+    }
+}
@@ -1,105 +1,308 @@
+use std::io;
+use std::mem::ManuallyDrop;
 use std::num::NonZeroUsize;
-use std::os::fd::{IntoRawFd, RawFd};
+use std::os::fd::{AsFd, BorrowedFd, IntoRawFd, OwnedFd, RawFd};
 use std::ptr::NonNull;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::time::{Duration, Instant};
 
+use nix::errno::Errno;
 use nix::fcntl::{OFlag, open};
+use nix::sys::memfd::{MFdFlags, memfd_create};
 use nix::sys::mman::{MapFlags, ProtFlags, mmap, munmap, shm_open, shm_unlink};
 use nix::sys::stat::fstat;
-use nix::unistd::{close, ftruncate};
+use nix::unistd::{close, dup, ftruncate};
 
+// 具名共享内存段开头的控制头，位于用户可见区域之前
+//
+// 新建的共享内存段内容全为 0，恰好是合法的初始状态：没有挂载者、未关闭。
+#[repr(C)]
+struct SegmentHeader {
+    // 以可写方式挂载该段的句柄数
+    attach_count: AtomicUsize,
+    // owner 正在关闭或已经释放，名字已被（或即将被）unlink
+    closing: AtomicBool,
+}
+
+// 控制头占用的字节数，保证其后的用户区域按 64 字节对齐
+const HEADER_SIZE: usize = 64;
+const _: () = assert!(size_of::<SegmentHeader>() <= HEADER_SIZE);
+
+/// POSIX 共享内存段的映射句柄
+///
+/// 同一个共享内存段应当**恰好有一个**句柄负责 unlink（即 owner）：
+/// `new()` 创建的句柄默认是 owner，`open()` 挂载的句柄不是。
+/// 若创建者需要先于读者退出，可调用 [`MemoryHandle::detach`] 放弃所有权，
+/// 或通过 `set_owner` 把所有权交给其他句柄。
+///
+/// 具名共享内存段开头有一个控制头（不计入 `size`，也不出现在 [`as_slice`](Self::as_slice)
+/// 中），记录以可写方式挂载该段的句柄数和“关闭中”标志，用于协调关闭：
+/// owner 可调用 [`shutdown`](Self::shutdown) 设置关闭标志，等其他挂载者离开后再 unlink 名字；
+/// 挂载者通过 [`is_closing`](Self::is_closing) 发现 owner 正在退出。等待有超时，
+/// 因此挂载者崩溃留下的陈旧计数不会阻止 owner 删除名字，重启后的创建者总能用同一个名字
+/// 创建新段。owner 直接释放（drop/`close`）时设置关闭标志并立即 unlink，不等待；
+/// 已有的映射在 unlink 后仍然有效，其余挂载者可以继续访问，直到各自释放。
+/// 只读句柄无法写入控制头，不参与计数。匿名段没有名字和控制头。
 pub struct MemoryHandle {
-    fd: RawFd,
+    // 0 也是合法的 fd（进程关闭 stdin 后 shm_open 可能返回 0），
+    // 因此用 Option 表示 fd 是否有效，而不是与 0 比较；释放时 take() 保证只关闭一次
+    fd: Option<RawFd>,
     name: String,
     owner: bool,
+    // 用户可见区域的大小和起始地址，映射本身从 ptr 之前的 header_len 字节开始
     size: NonZeroUsize,
     ptr: NonNull<u8>,
+    header_len: usize,
+    // 本句柄是否计入了控制头中的挂载数
+    attached: bool,
 }
 impl Drop for MemoryHandle {
     fn drop(&mut self) {
-        //解除内存映射
-        if let Err(e) = unsafe { munmap(self.ptr.cast(), self.size.get()) } {
-            eprintln!("Failed to unmap memory! {}", e);
-        }
-
-        if self.fd != 0 {
-            //释放内存
-            if self.owner {
-                if let Err(err) = shm_unlink(self.name.as_str()) {
-                    eprintln!("Failed to unlink shared memory: {}", err);
-                }
-            }
-            if let Err(err) = close(self.fd) {
-                eprintln!("Failed to close file descriptor: {}", err);
-            }
+        if let Err(e) = self.release() {
+            eprintln!("Failed to release shared memory {}: {}", self.name, e);
         }
     }
 }
 
 impl MemoryHandle {
     pub fn new<T: Into<String>>(name: T, size: usize) -> Result<Self, std::io::Error> {
+        Self::new_with_flags(name, size, MapFlags::empty())
+    }
+
+    /// 与 [`new`](MemoryHandle::new) 相同，但 mmap 时额外附加 flags（如 `MAP_POPULATE`）
+    ///
+    /// 具名共享内存段位于 tmpfs（/dev/shm），内核不允许对其使用 `MAP_HUGETLB`，
+    /// 此时返回“huge pages unavailable”错误且不会留下共享内存段；需要大页时使用
+    /// [`anonymous_with_flags`](MemoryHandle::anonymous_with_flags)。
+    pub fn new_with_flags<T: Into<String>>(
+        name: T,
+        size: usize,
+        flags: MapFlags,
+    ) -> Result<Self, std::io::Error> {
         let name = name.into();
         let path = std::path::Path::new("/dev/shm").join(&name[1..]);
         if path.exists() {
-            return MemoryHandle::open(name);
+            return MemoryHandle::open_with_flags(name, flags);
         }
         let fd = shm_open(
             name.as_str(),
             OFlag::O_CREAT | OFlag::O_RDWR, //创建并可读写
             nix::sys::stat::Mode::S_IRUSR | nix::sys::stat::Mode::S_IWUSR, //主有者可读写
         )?;
-        //设置共享内存大小
-        ftruncate(&fd, size as i64)?;
+        //设置共享内存大小，额外包含控制头
         let nz_size = NonZeroUsize::new(size).unwrap();
-        let ptr = unsafe {
-            //映射到进程的虚拟内存
-            mmap(
-                None, //为NULL，表示由系统选择映射地址
-                nz_size,
-                ProtFlags::PROT_READ | ProtFlags::PROT_WRITE, //可读可写
-                MapFlags::MAP_SHARED,                         //共享映射
-                &fd,                                          //文件描述符
-                0,
-            )?
+        let mapped = nz_size.saturating_add(HEADER_SIZE);
+        let ptr = match ftruncate(&fd, mapped.get() as i64)
+            .map_err(io::Error::from)
+            .and_then(|()| map_shared(&fd, mapped, flags))
+        {
+            Ok(ptr) => ptr,
+            Err(e) => {
+                // 映射失败时删除刚创建的共享内存段，避免残留
+                let _ = shm_unlink(name.as_str());
+                return Err(e);
+            }
         };
 
-        Ok(Self {
-            fd: fd.into_raw_fd(),
-            name,
-            owner: true,
-            size: nz_size,
-            ptr: ptr.cast(),
-        })
+        Ok(Self::attach(fd.into_raw_fd(), name, true, nz_size, ptr))
     }
 
     pub fn open<T: Into<String>>(name: T) -> Result<Self, std::io::Error> {
+        Self::open_with_flags(name, MapFlags::empty())
+    }
+
+    fn open_with_flags<T: Into<String>>(name: T, flags: MapFlags) -> Result<Self, std::io::Error> {
         let name = name.into();
         let fd = shm_open(
             name.as_str(),
             OFlag::O_RDWR,                 //可读写
             nix::sys::stat::Mode::S_IRUSR, //主有者可读
         )?;
-        let size = fstat(&fd).unwrap().st_size as usize;
-        let nz_size = NonZeroUsize::new(size).unwrap();
-        let ptr = unsafe {
-            //映射到进程的虚拟内存
-            mmap(
-                None, //为NULL，表示由系统选择映射地址
-                nz_size,
-                ProtFlags::PROT_READ | ProtFlags::PROT_WRITE, //可读可写
-                MapFlags::MAP_SHARED,                         //共享映射
-                &fd,                                          //文件描述符
-                0,
-            )?
+        let mapped = non_zero_size(fstat(&fd)?.st_size as usize)?;
+        let nz_size = user_size(mapped)?;
+        let ptr = map_shared(&fd, mapped, flags)?;
+
+        let handle = Self::attach(fd.into_raw_fd(), name, false, nz_size, ptr);
+        if handle.is_closing() {
+            // owner 正在 shutdown 时挂载到了旧段，已计入挂载数，通过 close() 离开
+            let _ = handle.close();
+            return Err(io::Error::new(
+                io::ErrorKind::NotFound,
+                "shared memory segment is shutting down",
+            ));
+        }
+        Ok(handle)
+    }
+
+    // 由映射了整个具名段（含控制头）的 ptr 构造句柄，并计入挂载数
+    fn attach(fd: RawFd, name: String, owner: bool, size: NonZeroUsize, ptr: NonNull<u8>) -> Self {
+        let handle = Self {
+            fd: Some(fd),
+            name,
+            owner,
+            size,
+            ptr: unsafe { ptr.add(HEADER_SIZE) },
+            header_len: HEADER_SIZE,
+            attached: true,
         };
+        if let Some(header) = handle.header() {
+            header.attach_count.fetch_add(1, Ordering::AcqRel);
+        }
+        handle
+    }
+    /// 以只读方式挂载已存在的共享内存段：fd 以 `O_RDONLY` 打开，映射为 `PROT_READ`
+    ///
+    /// 适用于只消费数据的订阅者。返回的句柄不是 owner，且只提供只读访问。
+    pub fn open_readonly<T: Into<String>>(name: T) -> io::Result<ReadOnlyMemoryHandle> {
+        let name = name.into();
+        let fd = shm_open(
+            name.as_str(),
+            OFlag::O_RDONLY,               //只读
+            nix::sys::stat::Mode::S_IRUSR, //主有者可读
+        )?;
+        let mapped = non_zero_size(fstat(&fd)?.st_size as usize)?;
+        let size = user_size(mapped)?;
+        let ptr = map_with_prot(&fd, mapped, ProtFlags::PROT_READ, MapFlags::empty())?;
 
-        Ok(Self {
-            fd: fd.into_raw_fd(),
+        Ok(ReadOnlyMemoryHandle(Self {
+            fd: Some(fd.into_raw_fd()),
             name,
             owner: false,
+            size,
+            ptr: unsafe { ptr.add(HEADER_SIZE) },
+            header_len: HEADER_SIZE,
+            attached: false,
+        }))
+    }
+
+    /// 用 `memfd_create` 创建匿名共享内存段，不占用 /dev/shm 中的名字，也无需 unlink
+    ///
+    /// fd 不带 close-on-exec 标志，可通过 fork/exec 继承给子进程，子进程用
+    /// [`MemoryHandle::from_fd`] 重新映射。所有引用该段的 fd 和映射释放后内存自动回收。
+    pub fn anonymous(size: usize) -> io::Result<Self> {
+        Self::anonymous_with_flags(size, MapFlags::empty())
+    }
+
+    /// 与 [`anonymous`](MemoryHandle::anonymous) 相同，但 mmap 时额外附加 flags
+    ///
+    /// flags 包含 `MAP_HUGETLB` 时使用大页创建 memfd，size 须为大页大小的整数倍；
+    /// 内核未预留大页（`vm.nr_hugepages` 为 0）等情况下返回“huge pages unavailable”错误。
+    pub fn anonymous_with_flags(size: usize, flags: MapFlags) -> io::Result<Self> {
+        let nz_size = non_zero_size(size)?;
+        let huge = flags.contains(MapFlags::MAP_HUGETLB);
+        let memfd_flags = if huge {
+            MFdFlags::MFD_HUGETLB
+        } else {
+            MFdFlags::empty()
+        };
+        let created = memfd_create("zenrc-shm", memfd_flags)
+            .and_then(|fd| ftruncate(&fd, size as i64).map(|()| fd));
+        let fd = created.map_err(|e| huge_pages_error(e, flags))?;
+        Self::map_fd(fd, nz_size, flags)
+    }
+
+    /// 由已有的共享内存 fd（如继承自父进程的匿名段）构造句柄，接管 fd 的所有权
+    ///
+    /// size 不能超过 fd 对应文件的大小，否则返回 `InvalidInput`。得到的句柄没有名字，
+    /// 不会 unlink 任何共享内存段。
+    pub fn from_fd(fd: OwnedFd, size: usize) -> io::Result<Self> {
+        let nz_size = non_zero_size(size)?;
+        let len = fstat(&fd)?.st_size as usize;
+        if len < size {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("shared memory fd holds {} bytes, {} requested", len, size),
+            ));
+        }
+        Self::map_fd(fd, nz_size, MapFlags::empty())
+    }
+
+    fn map_fd(fd: OwnedFd, nz_size: NonZeroUsize, flags: MapFlags) -> io::Result<Self> {
+        let ptr = map_shared(&fd, nz_size, flags)?;
+        Ok(Self {
+            fd: Some(fd.into_raw_fd()),
+            name: String::new(),
+            owner: false,
             size: nz_size,
-            ptr: ptr.cast(),
+            ptr,
+            header_len: 0,
+            attached: false,
         })
     }
+
+    /// 复制一个指向同一共享内存段的新句柄：dup fd 并建立独立的映射
+    ///
+    /// 克隆出的句柄不是 owner，可交给其他线程单独使用；具名段的克隆同样计入挂载数。
+    pub fn try_clone(&self) -> io::Result<MemoryHandle> {
+        let fd = dup(self.as_fd())?;
+        let ptr = map_shared(&fd, self.size.saturating_add(self.header_len), MapFlags::empty())?;
+        if self.header_len == 0 {
+            return Ok(Self {
+                fd: Some(fd.into_raw_fd()),
+                name: self.name.clone(),
+                owner: false,
+                size: self.size,
+                ptr,
+                header_len: 0,
+                attached: false,
+            });
+        }
+        Ok(Self::attach(fd.into_raw_fd(), self.name.clone(), false, self.size, ptr))
+    }
+
+    /// 解除映射并关闭 fd，owner 还会 unlink 共享内存段
+    ///
+    /// 与 drop 不同，错误会返回给调用者；unlink 时共享内存段已不存在（ENOENT）视为成功。
+    pub fn close(self) -> io::Result<()> {
+        let mut this = ManuallyDrop::new(self);
+        let result = this.release();
+        drop(std::mem::take(&mut this.name));
+        result
+    }
+
+    /// 放弃所有权并解除本地映射，无论 owner 标志如何都**不会**主动关闭共享内存段
+    ///
+    /// 共享内存段会一直保留，直到另一个 owner 句柄被释放或手动调用 [`unlink`]。
+    pub fn detach(mut self) -> io::Result<()> {
+        self.owner = false;
+        self.close()
+    }
+
+    // 依次离开挂载、解除映射、unlink、关闭 fd，返回遇到的第一个错误
+    //
+    // owner 离开时设置关闭标志并 unlink，不等待其他挂载者
+    fn release(&mut self) -> io::Result<()> {
+        if std::mem::take(&mut self.attached)
+            && let Some(header) = self.header()
+        {
+            if self.owner {
+                header.closing.store(true, Ordering::Release);
+            }
+            header.attach_count.fetch_sub(1, Ordering::AcqRel);
+        }
+
+        let mut result = Ok(());
+        //解除内存映射
+        let base = unsafe { self.ptr.sub(self.header_len) };
+        if let Err(e) = unsafe { munmap(base.cast(), self.size.get() + self.header_len) } {
+            result = Err(e.into());
+        }
+
+        if let Some(fd) = self.fd.take() {
+            //释放内存，匿名段没有名字，无需 unlink
+            if self.owner && !self.name.is_empty() {
+                match shm_unlink(self.name.as_str()) {
+                    Ok(()) | Err(Errno::ENOENT) => {}
+                    Err(e) => result = result.and(Err(e.into())),
+                }
+            }
+            if let Err(e) = close(fd) {
+                result = result.and(Err(e.into()));
+            }
+        }
+        result
+    }
+
     pub fn get_mut_ptr(&mut self) -> NonNull<u8> {
         self.ptr
     }
@@ -109,4 +312,393 @@ impl MemoryHandle {
     pub fn is_owner(&self) -> bool {
         self.owner
     }
+
+    // 具名段的控制头，匿名段没有控制头
+    fn header(&self) -> Option<&SegmentHeader> {
+        if self.header_len == 0 {
+            return None;
+        }
+        // 控制头位于映射起点，映射在句柄存活期间有效
+        Some(unsafe { &*self.ptr.sub(self.header_len).cast::<SegmentHeader>().as_ptr() })
+    }
+
+    /// 以可写方式挂载该段的句柄数（跨进程），可用于发现无人使用或挂载过多的共享内存段
+    ///
+    /// `new`/`open`/`try_clone` 在映射成功后才加一，释放时在解除映射之前减一，
+    /// 因此进程崩溃只会让计数偏大（残留的陈旧计数），而不会偏小：
+    /// 计数为 0 时确实没有存活的可写挂载者。只读句柄不计入；匿名段没有控制头，始终返回 0。
+    pub fn attach_count(&self) -> usize {
+        self.header().map_or(0, |h| h.attach_count.load(Ordering::Acquire))
+    }
+
+    /// owner 是否正在关闭或已经释放了该共享内存段，挂载者看到 true 后应尽快释放句柄
+    ///
+    /// 匿名段始终返回 false。
+    pub fn is_closing(&self) -> bool {
+        self.header().is_some_and(|h| h.closing.load(Ordering::Acquire))
+    }
+
+    /// owner 通知挂载者关闭：设置关闭标志，最多等待 timeout 让其他挂载者离开，不释放本句柄
+    ///
+    /// 返回是否在超时前等到其他挂载者全部离开。名字在本句柄释放时才 unlink，
+    /// 等待期间新的挂载（包括同名的 `new`）会因关闭标志而失败。段中有需要由 owner 销毁的
+    /// 共享对象（如 [`MpmcRingBuffer`](crate::ringbuffer::MpmcRingBuffer) 中的锁）时，
+    /// 先调用本方法，确认挂载者已离开后再销毁它们，最后释放句柄。
+    /// 等待期间以 1ms 间隔轮询控制头中的挂载数。非 owner 句柄和匿名段直接返回 true。
+    pub fn begin_shutdown(&self, timeout: Duration) -> bool {
+        let Some(header) = self.header().filter(|_| self.owner) else {
+            return true;
+        };
+        header.closing.store(true, Ordering::Release);
+        let deadline = Instant::now() + timeout;
+        while header.attach_count.load(Ordering::Acquire) > 1 {
+            if Instant::now() >= deadline {
+                return false;
+            }
+            std::thread::sleep(Duration::from_millis(1));
+        }
+        true
+    }
+
+    /// owner 协调关闭：先通知挂载者并等待它们离开（见 [`begin_shutdown`](Self::begin_shutdown)），
+    /// 再 unlink 名字并释放本句柄
+    ///
+    /// 返回是否在超时前等到其他挂载者全部离开；超时仍未离开的挂载者不受影响，
+    /// 可以继续访问已有的映射。非 owner 句柄和匿名段不等待，直接释放。
+    pub fn shutdown(self, timeout: Duration) -> io::Result<bool> {
+        let detached = self.begin_shutdown(timeout);
+        self.close().map(|()| detached)
+    }
+
+    /// 整段映射内存的只读切片，长度为映射大小
+    ///
+    /// 其他进程可能同时修改这段内存，读取到的内容需要自行通过同步原语保证一致。
+    pub fn as_slice(&self) -> &[u8] {
+        // 映射在句柄存活期间有效，长度即映射大小
+        unsafe { std::slice::from_raw_parts(self.ptr.as_ptr(), self.size.get()) }
+    }
+
+    /// 整段映射内存的可写切片，长度为映射大小
+    pub fn as_mut_slice(&mut self) -> &mut [u8] {
+        unsafe { std::slice::from_raw_parts_mut(self.ptr.as_ptr(), self.size.get()) }
+    }
+
+    /// 把 data 写入 offset 处，超出映射范围时返回 `InvalidInput` 且不写入任何字节
+    pub fn write_at(&mut self, offset: usize, data: &[u8]) -> io::Result<()> {
+        let size = self.size.get();
+        let end = offset
+            .checked_add(data.len())
+            .filter(|&end| end <= size)
+            .ok_or_else(|| {
+                io::Error::new(
+                    io::ErrorKind::InvalidInput,
+                    format!(
+                        "write of {} bytes at offset {} exceeds shared memory size {}",
+                        data.len(),
+                        offset,
+                        size
+                    ),
+                )
+            })?;
+        self.as_mut_slice()[offset..end].copy_from_slice(data);
+        Ok(())
+    }
+}
+
+/// 只读映射的共享内存句柄，由 [`MemoryHandle::open_readonly`] 创建
+///
+/// 映射为 `PROT_READ`，因此不提供 `get_mut_ptr` 等可写访问：
+///
+/// ```compile_fail
+/// use zenrc_shm::shm::MemoryHandle;
+///
+/// let mut handle = MemoryHandle::open_readonly("/my_shared_mem").unwrap();
+/// let ptr = handle.get_mut_ptr();
+/// ```
+pub struct ReadOnlyMemoryHandle(MemoryHandle);
+
+impl ReadOnlyMemoryHandle {
+    /// 映射起始地址，只能用于读取
+    pub fn get_ptr(&self) -> NonNull<u8> {
+        self.0.ptr
+    }
+
+    /// 整段映射内存的只读切片，长度为映射大小
+    pub fn as_slice(&self) -> &[u8] {
+        self.0.as_slice()
+    }
+
+    /// 解除映射并关闭 fd，不会 unlink 共享内存段
+    pub fn close(self) -> io::Result<()> {
+        self.0.close()
+    }
+}
+
+impl AsFd for MemoryHandle {
+    fn as_fd(&self) -> BorrowedFd<'_> {
+        let fd = self.fd.expect("live MemoryHandle always holds a valid fd");
+        // fd 在句柄存活期间一直有效
+        unsafe { BorrowedFd::borrow_raw(fd) }
+    }
+}
+
+/// 按名字删除共享内存段，段不存在（ENOENT）视为成功
+///
+/// 用于清理进程崩溃后残留的共享内存段，避免之后的 [`MemoryHandle::new`] 挂载到旧数据。
+/// 已映射该段的进程仍可继续访问自己的映射。
+pub fn unlink(name: &str) -> io::Result<()> {
+    match shm_unlink(name) {
+        Ok(()) | Err(Errno::ENOENT) => Ok(()),
+        Err(e) => Err(e.into()),
+    }
+}
+
+// 具名段映射大小扣除控制头后的用户区域大小
+fn user_size(mapped: NonZeroUsize) -> io::Result<NonZeroUsize> {
+    NonZeroUsize::new(mapped.get().saturating_sub(HEADER_SIZE)).ok_or_else(|| {
+        io::Error::new(
+            io::ErrorKind::InvalidData,
+            "shared memory segment is too small to hold the control header",
+        )
+    })
+}
+
+fn non_zero_size(size: usize) -> io::Result<NonZeroUsize> {
+    NonZeroUsize::new(size)
+        .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, "shared memory size is zero"))
+}
+
+// 大页相关的失败附加说明，便于与其他 IO 错误区分
+fn huge_pages_error(err: Errno, flags: MapFlags) -> io::Error {
+    let err = io::Error::from(err);
+    if flags.contains(MapFlags::MAP_HUGETLB) {
+        io::Error::new(err.kind(), format!("huge pages unavailable: {}", err))
+    } else {
+        err
+    }
+}
+
+// 把 fd 以可读写的共享方式映射到进程的虚拟内存，flags 附加在 MAP_SHARED 之上
+fn map_shared(fd: impl AsFd, size: NonZeroUsize, flags: MapFlags) -> io::Result<NonNull<u8>> {
+    map_with_prot(fd, size, ProtFlags::PROT_READ | ProtFlags::PROT_WRITE, flags)
+}
+
+fn map_with_prot(
+    fd: impl AsFd,
+    size: NonZeroUsize,
+    prot: ProtFlags,
+    flags: MapFlags,
+) -> io::Result<NonNull<u8>> {
+    let ptr = unsafe {
+        mmap(
+            None, //为NULL，表示由系统选择映射地址
+            size,
+            prot,
+            MapFlags::MAP_SHARED | flags, //共享映射
+            fd,                           //文件描述符
+            0,
+        )
+        .map_err(|e| huge_pages_error(e, flags))?
+    };
+    Ok(ptr.cast())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn shm_exists(name: &str) -> bool {
+        std::path::Path::new("/dev/shm").join(&name[1..]).exists()
+    }
+
+    #[test]
+    fn detach_keeps_segment_alive() {
+        let name = format!("/zenrc_test_detach_{}", std::process::id());
+        let creator = MemoryHandle::new(name.as_str(), 4096).unwrap();
+        assert!(creator.is_owner());
+        creator.detach().unwrap();
+        assert!(shm_exists(&name));
+
+        let mut reader = MemoryHandle::open(name.as_str()).unwrap();
+        reader.set_owner(true);
+        reader.close().unwrap();
+        assert!(!shm_exists(&name));
+    }
+
+    #[test]
+    fn try_clone_shares_bytes() {
+        let name = format!("/zenrc_test_clone_{}", std::process::id());
+        let mut handle = MemoryHandle::new(name.as_str(), 4096).unwrap();
+        let mut clone = handle.try_clone().unwrap();
+        assert!(!clone.is_owner());
+        assert_ne!(handle.get_mut_ptr(), clone.get_mut_ptr());
+
+        unsafe {
+            std::ptr::copy_nonoverlapping(b"zenrc".as_ptr(), handle.get_mut_ptr().as_ptr(), 5);
+            let read = std::slice::from_raw_parts(clone.get_mut_ptr().as_ptr(), 5);
+            assert_eq!(read, b"zenrc");
+        }
+        drop(clone);
+        handle.close().unwrap();
+    }
+
+    #[test]
+    fn open_readonly_sees_writer_bytes() {
+        let name = format!("/zenrc_test_readonly_{}", std::process::id());
+        let mut writer = MemoryHandle::new(name.as_str(), 4096).unwrap();
+        let reader = MemoryHandle::open_readonly(name.as_str()).unwrap();
+
+        writer.write_at(0, b"zenrc").unwrap();
+        assert_eq!(&reader.as_slice()[..5], b"zenrc");
+        assert_eq!(unsafe { reader.get_ptr().as_ptr().read() }, b'z');
+
+        reader.close().unwrap();
+        writer.close().unwrap();
+        assert!(!shm_exists(&name));
+    }
+
+    #[test]
+    fn unlink_removes_leaked_segment() {
+        let name = format!("/zenrc_test_unlink_{}", std::process::id());
+        // 模拟进程崩溃：owner 句柄没有被 drop
+        std::mem::forget(MemoryHandle::new(name.as_str(), 4096).unwrap());
+        assert!(shm_exists(&name));
+
+        unlink(&name).unwrap();
+        assert!(!shm_exists(&name));
+        // 再次删除不存在的段不报错
+        unlink(&name).unwrap();
+    }
+
+    #[test]
+    fn write_at_checks_bounds() {
+        let mut handle = MemoryHandle::anonymous(4096).unwrap();
+        handle.write_at(4091, b"zenrc").unwrap();
+        assert_eq!(&handle.as_slice()[4091..], b"zenrc");
+        assert_eq!(handle.as_slice().len(), 4096);
+
+        let err = handle.write_at(4092, b"zenrc").unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidInput);
+        let err = handle.write_at(usize::MAX, b"z").unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidInput);
+        // 越界写入不会改动已有内容
+        assert_eq!(&handle.as_slice()[4091..], b"zenrc");
+    }
+
+    #[test]
+    fn anonymous_segment_maps_through_from_fd() {
+        let mut anon = MemoryHandle::anonymous(4096).unwrap();
+        let fd = anon.as_fd().try_clone_to_owned().unwrap();
+        let mut other = MemoryHandle::from_fd(fd, 4096).unwrap();
+
+        unsafe {
+            std::ptr::copy_nonoverlapping(b"memfd".as_ptr(), anon.get_mut_ptr().as_ptr(), 5);
+            let read = std::slice::from_raw_parts(other.get_mut_ptr().as_ptr(), 5);
+            assert_eq!(read, b"memfd");
+        }
+
+        let fd = anon.as_fd().try_clone_to_owned().unwrap();
+        let err = MemoryHandle::from_fd(fd, 8192).err().unwrap();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidInput);
+        other.close().unwrap();
+        anon.close().unwrap();
+    }
+
+    #[test]
+    fn attach_count_tracks_open_handles() {
+        let name = format!("/zenrc_test_attach_{}", std::process::id());
+        let owner = MemoryHandle::new(name.as_str(), 4096).unwrap();
+        let reader = MemoryHandle::open(name.as_str()).unwrap();
+        assert_eq!(owner.attach_count(), 2);
+        assert_eq!(reader.attach_count(), 2);
+        // 只读句柄不计入
+        let readonly = MemoryHandle::open_readonly(name.as_str()).unwrap();
+        assert_eq!(owner.attach_count(), 2);
+
+        readonly.close().unwrap();
+        reader.close().unwrap();
+        assert_eq!(owner.attach_count(), 1);
+        owner.close().unwrap();
+        assert_eq!(MemoryHandle::anonymous(4096).unwrap().attach_count(), 0);
+    }
+
+    #[test]
+    fn shutdown_waits_for_readers_before_unlinking() {
+        let name = format!("/zenrc_test_shutdown_{}", std::process::id());
+        let owner = MemoryHandle::new(name.as_str(), 4096).unwrap();
+
+        let (attached_tx, attached_rx) = std::sync::mpsc::channel();
+        let reader_name = name.clone();
+        let reader = std::thread::spawn(move || {
+            let mut reader = MemoryHandle::open(reader_name.as_str()).unwrap();
+            attached_tx.send(()).unwrap();
+            while !reader.is_closing() {
+                std::thread::sleep(Duration::from_millis(1));
+            }
+            // 读者仍挂载时名字不会被删除，也不再接受新的挂载
+            std::thread::sleep(Duration::from_millis(20));
+            let still_linked = shm_exists(&reader_name);
+            let err = MemoryHandle::open(reader_name.as_str()).err().unwrap();
+            reader.write_at(0, b"zenrc").unwrap();
+            reader.close().unwrap();
+            (still_linked, err.kind())
+        });
+
+        attached_rx.recv().unwrap();
+        assert!(owner.shutdown(Duration::from_secs(5)).unwrap());
+        assert_eq!(reader.join().unwrap(), (true, io::ErrorKind::NotFound));
+        assert!(!shm_exists(&name));
+    }
+
+    #[test]
+    fn shutdown_unlinks_after_timeout() {
+        let name = format!("/zenrc_test_shutdown_timeout_{}", std::process::id());
+        let owner = MemoryHandle::new(name.as_str(), 4096).unwrap();
+        let reader = MemoryHandle::open(name.as_str()).unwrap();
+
+        // 读者没有在超时前离开，owner 仍然删除名字，读者可以继续访问已有的映射
+        assert!(!owner.shutdown(Duration::from_millis(5)).unwrap());
+        assert!(reader.is_closing());
+        assert!(!shm_exists(&name));
+        reader.close().unwrap();
+    }
+
+    #[test]
+    fn stale_attach_count_does_not_block_recreation() {
+        let name = format!("/zenrc_test_stale_{}", std::process::id());
+        let owner = MemoryHandle::new(name.as_str(), 4096).unwrap();
+        // 模拟读者进程崩溃：句柄没有被释放，计数残留
+        std::mem::forget(MemoryHandle::open(name.as_str()).unwrap());
+        assert_eq!(owner.attach_count(), 2);
+        drop(owner);
+        assert!(!shm_exists(&name));
+
+        // 重启后的创建者得到一个新段
+        let restarted = MemoryHandle::new(name.as_str(), 4096).unwrap();
+        assert!(restarted.is_owner());
+        assert!(!restarted.is_closing());
+        assert_eq!(restarted.attach_count(), 1);
+        restarted.close().unwrap();
+        assert!(!shm_exists(&name));
+    }
+
+    #[test]
+    fn huge_pages_map_or_report_unavailable() {
+        const HUGE_PAGE: usize = 2 << 20;
+        match MemoryHandle::anonymous_with_flags(HUGE_PAGE, MapFlags::MAP_HUGETLB) {
+            Ok(mut handle) => unsafe {
+                handle.get_mut_ptr().as_ptr().write(7);
+                assert_eq!(handle.get_mut_ptr().as_ptr().read(), 7);
+            },
+            Err(e) => assert!(e.to_string().contains("huge pages unavailable"), "{}", e),
+        }
+
+        // tmpfs 上的具名段不支持大页，失败后不应残留共享内存段
+        let name = format!("/zenrc_test_huge_{}", std::process::id());
+        let err = MemoryHandle::new_with_flags(name.as_str(), HUGE_PAGE, MapFlags::MAP_HUGETLB)
+            .err()
+            .unwrap();
+        assert!(err.to_string().contains("huge pages unavailable"), "{}", err);
+        assert!(!shm_exists(&name));
+    }
 }
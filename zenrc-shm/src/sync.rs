@@ -3,17 +3,31 @@
  */
 use std::{
     cell::UnsafeCell,
+    marker::PhantomData,
     ops::{Deref, DerefMut},
     ptr::NonNull,
 };
 
 use nix::libc::{
-    PTHREAD_PROCESS_SHARED, pthread_cond_t, pthread_mutex_t, pthread_mutexattr_t, pthread_rwlock_t,
-    pthread_rwlockattr_t, timespec,
+    PTHREAD_PROCESS_SHARED, pthread_cond_t, pthread_condattr_t, pthread_mutex_t,
+    pthread_mutexattr_t, pthread_rwlock_t, pthread_rwlockattr_t, timespec,
 };
 
 use crate::errors::*;
 
+// libc crate 未导出 Linux 下 rwlock 的超时加锁函数，直接声明 glibc 中的符号
+#[cfg(target_os = "linux")]
+unsafe extern "C" {
+    fn pthread_rwlock_timedrdlock(
+        lock: *mut pthread_rwlock_t,
+        abstime: *const timespec,
+    ) -> std::ffi::c_int;
+    fn pthread_rwlock_timedwrlock(
+        lock: *mut pthread_rwlock_t,
+        abstime: *const timespec,
+    ) -> std::ffi::c_int;
+}
+
 /// 超时设置
 pub enum Timeout {
     Infinite,
@@ -46,9 +60,12 @@ impl<'t, T> DerefMut for SharedMutexGuard<'t, T> {
 }
 
 /// 共享读写锁的读守护结构
+///
+/// 与 std 的守护对象一样不是 `Send`：解锁必须在加锁的线程上进行。
 pub struct SharedRwLockReadGuard<'t, T> {
     data: NonNull<T>,
     lock: &'t *mut pthread_rwlock_t,
+    _not_send: PhantomData<*const ()>,
 }
 impl<'t, T> Drop for SharedRwLockReadGuard<'t, T> {
     fn drop(&mut self) {
@@ -61,6 +78,7 @@ impl<'t, T> SharedRwLockReadGuard<'t, T> {
         Self {
             lock: &lock.ptr,
             data: unsafe { NonNull::new_unchecked(*lock.data.get()) },
+            _not_send: PhantomData,
         }
     }
 }
@@ -72,8 +90,23 @@ impl<'t, T> Deref for SharedRwLockReadGuard<'t, T> {
 }
 
 /// 共享读写锁的写守护结构
+///
+/// 与 std 的守护对象一样不是 `Send`：解锁必须在加锁的线程上进行，
+/// 在其他线程上调用 `pthread_rwlock_unlock` 是未定义行为。
+///
+/// ```compile_fail
+/// use zenrc_shm::sync::{SharedRwLock, SharedRwLockWriteGuard};
+///
+/// fn assert_send<T: Send>(_: T) {}
+///
+/// fn send_guard(lock: &SharedRwLock<u32>) {
+///     let guard: SharedRwLockWriteGuard<'_, u32> = lock.write().unwrap();
+///     assert_send(guard);
+/// }
+/// ```
 pub struct SharedRwLockWriteGuard<'t, T> {
     lock: &'t SharedRwLock<T>,
+    _not_send: PhantomData<*const ()>,
 }
 impl<'t, T> Drop for SharedRwLockWriteGuard<'t, T> {
     fn drop(&mut self) {
@@ -83,7 +116,10 @@ impl<'t, T> Drop for SharedRwLockWriteGuard<'t, T> {
 }
 impl<'t, T> SharedRwLockWriteGuard<'t, T> {
     fn new(lock: &'t SharedRwLock<T>) -> Self {
-        Self { lock }
+        Self {
+            lock,
+            _not_send: PhantomData,
+        }
     }
 }
 impl<'t, T> Deref for SharedRwLockWriteGuard<'t, T> {
@@ -98,9 +134,124 @@ impl<'t, T> DerefMut for SharedRwLockWriteGuard<'t, T> {
     }
 }
 /// 用于进程间同步的共享条件变量结构
+///
+/// 与 [`SharedMutex`] 配合使用：持有互斥锁检查条件，不满足时调用 wait 释放锁并挂起，
+/// 被唤醒后重新持有锁再次检查条件。
 pub struct SharedCondVar {
     ptr: *mut pthread_cond_t,
-    data: UnsafeCell<*mut u8>,
+}
+
+impl Drop for SharedCondVar {
+    fn drop(&mut self) {
+        unsafe {
+            nix::libc::pthread_cond_destroy(self.ptr);
+        }
+    }
+}
+
+impl SharedCondVar {
+    /// 在提供的缓冲区中初始化条件变量，并返回使用的字节数
+    ///
+    /// # Safety
+    ///
+    /// mem 之后必须有足够容纳对齐填充和 `pthread_cond_t` 的可写内存。
+    pub unsafe fn new(mem: *mut u8) -> Result<(Self, usize), CondVarError> {
+        unsafe {
+            let padding = mem.align_offset(std::mem::size_of::<*mut u8>() as _);
+            let mut cond_attr = std::mem::MaybeUninit::<pthread_condattr_t>::uninit();
+            match nix::libc::pthread_condattr_init(cond_attr.as_mut_ptr()) {
+                0 => {}
+                err_code => {
+                    return Err(CondVarError::InitError(err_code));
+                }
+            }
+            // 设置条件变量属性为进程间共享，时钟保持默认的 CLOCK_REALTIME，与 deadline 一致
+            let cond_attr = cond_attr.assume_init_mut();
+            match nix::libc::pthread_condattr_setpshared(cond_attr, PTHREAD_PROCESS_SHARED) {
+                0 => {}
+                err_code => {
+                    return Err(CondVarError::InitError(err_code));
+                }
+            }
+            let ptr = mem.add(padding) as *mut pthread_cond_t;
+            match nix::libc::pthread_cond_init(ptr, cond_attr) {
+                0 => {}
+                err_code => {
+                    return Err(CondVarError::InitError(err_code));
+                }
+            }
+            Ok((Self { ptr }, padding + std::mem::size_of::<pthread_cond_t>()))
+        }
+    }
+
+    /// 从已初始化的内存位置重用条件变量，并返回使用的字节数
+    ///
+    /// # Safety
+    ///
+    /// mem 处必须是已由 [`new`](SharedCondVar::new) 初始化的条件变量。
+    pub unsafe fn try_into(mem: *mut u8) -> (Self, usize) {
+        unsafe {
+            let padding = mem.align_offset(std::mem::size_of::<*mut u8>() as _);
+            let ptr = mem.add(padding) as *mut pthread_cond_t;
+            (Self { ptr }, padding + std::mem::size_of::<pthread_cond_t>())
+        }
+    }
+
+    /// 释放 guard 对应的互斥锁并等待唤醒，返回时重新持有锁
+    ///
+    /// 可能出现虚假唤醒，调用方应在循环中重新检查条件。
+    pub fn wait<'t, T>(
+        &self,
+        guard: SharedMutexGuard<'t, T>,
+    ) -> Result<SharedMutexGuard<'t, T>, CondVarError> {
+        unsafe {
+            match nix::libc::pthread_cond_wait(self.ptr, guard.lock.ptr) {
+                0 => Ok(guard),
+                err_code => Err(CondVarError::WaitError(err_code)),
+            }
+        }
+    }
+
+    /// 与 [`wait`](SharedCondVar::wait) 相同，但最多等待 timeout
+    ///
+    /// 返回重新持有的锁以及是否因超时返回。
+    pub fn wait_timeout<'t, T>(
+        &self,
+        guard: SharedMutexGuard<'t, T>,
+        timeout: Timeout,
+    ) -> Result<(SharedMutexGuard<'t, T>, bool), CondVarError> {
+        let timespec = match timeout {
+            Timeout::Infinite => return self.wait(guard).map(|guard| (guard, false)),
+            Timeout::Val(dur) => deadline(dur),
+        };
+        unsafe {
+            match nix::libc::pthread_cond_timedwait(self.ptr, guard.lock.ptr, &timespec) {
+                0 => Ok((guard, false)),
+                nix::libc::ETIMEDOUT => Ok((guard, true)),
+                err_code => Err(CondVarError::WaitError(err_code)),
+            }
+        }
+    }
+
+    /// 唤醒一个等待者
+    pub fn notify_one(&self) -> Result<(), CondVarError> {
+        unsafe {
+            match nix::libc::pthread_cond_signal(self.ptr) {
+                0 => Ok(()),
+                err_code => Err(CondVarError::NotifyError(err_code)),
+            }
+        }
+    }
+
+    /// 唤醒全部等待者
+    pub fn notify_all(&self) -> Result<(), CondVarError> {
+        unsafe {
+            match nix::libc::pthread_cond_broadcast(self.ptr) {
+                0 => Ok(()),
+                err_code => Err(CondVarError::NotifyError(err_code)),
+            }
+        }
+    }
 }
 
 /// 用于进程间同步的共享互斥锁结构
@@ -119,7 +270,11 @@ impl<T> Drop for SharedMutex<T> {
 
 impl<T> SharedMutex<T> {
     /// 在提供的缓冲区中初始化锁的新实例，并返回使用的字节数
-    unsafe fn new(mem: *mut u8, data: T) -> Result<(Self, usize), MutexLockError> {
+    ///
+    /// # Safety
+    ///
+    /// mem 之后必须有足够容纳对齐填充、`pthread_mutex_t` 和 T 的可写内存。
+    pub unsafe fn new(mem: *mut u8, data: T) -> Result<(Self, usize), MutexLockError> {
         unsafe {
             // 计算在当前内存地址 mem 之后，需要填充（padding）多少字节才能使接下来的数据对齐到指针 (*mut u8) 的边界上
             let padding = mem.align_offset(std::mem::size_of::<*mut u8>() as _);
@@ -164,7 +319,11 @@ impl<T> SharedMutex<T> {
     }
 
     /// 从已初始化的内存位置重用锁，并返回使用的字节数
-    unsafe fn try_into(mem: *mut u8) -> (Self, usize) {
+    ///
+    /// # Safety
+    ///
+    /// mem 处必须是已由 [`new`](SharedMutex::new) 初始化的锁。
+    pub unsafe fn try_into(mem: *mut u8) -> (Self, usize) {
         unsafe {
             let padding = mem.align_offset(std::mem::size_of::<*mut u8>() as _);
             let ptr = mem.add(padding) as *mut pthread_mutex_t;
@@ -185,7 +344,7 @@ impl<T> SharedMutex<T> {
     }
 
     /// Acquires the lock
-    fn lock(&self) -> Result<SharedMutexGuard<'_, T>, MutexLockError> {
+    pub fn lock(&self) -> Result<SharedMutexGuard<'_, T>, MutexLockError> {
         unsafe {
             match nix::libc::pthread_mutex_lock(self.ptr) {
                 0 => Ok(SharedMutexGuard::new(self)),
@@ -194,7 +353,7 @@ impl<T> SharedMutex<T> {
         }
     }
 
-    fn try_lock(&self) -> Result<SharedMutexGuard<'_, T>, MutexLockError> {
+    pub fn try_lock(&self) -> Result<SharedMutexGuard<'_, T>, MutexLockError> {
         unsafe {
             match nix::libc::pthread_mutex_trylock(self.ptr) {
                 0 => Ok(SharedMutexGuard::new(self)),
@@ -205,20 +364,10 @@ impl<T> SharedMutex<T> {
 
     #[cfg(target_os = "linux")]
     /// Acquires lock with timeout
-    fn time_lock(&self, timeout: Timeout) -> Result<SharedMutexGuard<'_, T>, MutexLockError> {
-        // For simplicity, we ignore timeout and just try to lock
+    pub fn time_lock(&self, timeout: Timeout) -> Result<SharedMutexGuard<'_, T>, MutexLockError> {
         let timespec = match timeout {
             Timeout::Infinite => return self.lock(),
-            Timeout::Val(dur) => {
-                // 计算超时时间点
-                let now = std::time::SystemTime::now();
-                let cur_time = now + dur;
-                let since_epoch = cur_time.duration_since(std::time::UNIX_EPOCH).unwrap();
-                timespec {
-                    tv_sec: since_epoch.as_secs() as _,
-                    tv_nsec: since_epoch.subsec_nanos() as _,
-                }
-            }
+            Timeout::Val(dur) => deadline(dur),
         };
         unsafe {
             match nix::libc::pthread_mutex_timedlock(self.ptr, &timespec) {
@@ -245,16 +394,33 @@ impl<T> SharedMutex<T> {
 }
 
 /// 用于进程间同步的共享读写锁结构
+///
+/// 只有 [`new`](Self::new) 初始化锁的句柄在析构时销毁锁，[`try_into`](Self::try_into)
+/// 挂载的句柄析构时不影响其他句柄（包括其他进程中的句柄）继续使用这把锁。
 pub struct SharedRwLock<T> {
     ptr: *mut pthread_rwlock_t,
     data: UnsafeCell<*mut T>,
+    // 由 new 初始化了锁，析构时负责销毁
+    owner: bool,
 }
 
+// SAFETY: 句柄只持有指向共享内存的指针，移动到其他线程后指向的仍是同一把锁和同一份数据；
+// 其中的 pthread 读写锁以 PTHREAD_PROCESS_SHARED 初始化，可以在任意线程加锁和解锁。
+// 只有初始化锁的句柄析构时销毁锁，挂载的句柄在任意线程析构都不会销毁其他句柄仍在使用的锁；
+// 初始化者须在其他句柄都不再使用锁之后才析构，这一点与跨进程共享时相同，与所在线程无关。
+// 数据只能通过锁保护的 guard 访问，与 std::sync::RwLock 相同，T: Send 即可转移句柄。
+unsafe impl<T: Send> Send for SharedRwLock<T> {}
+// SAFETY: 多个线程共享 &SharedRwLock 时，读锁允许并发得到 &T，写锁独占得到 &mut T，
+// 因此与 std::sync::RwLock 一样要求 T: Send + Sync。
+unsafe impl<T: Send + Sync> Sync for SharedRwLock<T> {}
+
 impl<T> Drop for SharedRwLock<T> {
     fn drop(&mut self) {
         println!("SharedRwLock::drop called");
-        unsafe {
-            nix::libc::pthread_rwlock_destroy(self.ptr);
+        if self.owner {
+            unsafe {
+                nix::libc::pthread_rwlock_destroy(self.ptr);
+            }
         }
     }
 }
@@ -294,6 +460,7 @@ impl<T> SharedRwLock<T> {
             let shared_rwlock = Self {
                 ptr,
                 data: UnsafeCell::new(data_ptr),
+                owner: true,
             };
             Ok((
                 shared_rwlock,
@@ -310,6 +477,7 @@ impl<T> SharedRwLock<T> {
             let shared_rwlock = Self {
                 ptr,
                 data: UnsafeCell::new(data_ptr),
+                owner: false,
             };
             //TODO: 检查指针有效性
             if ptr.is_null() || data_ptr.is_null() {
@@ -322,6 +490,11 @@ impl<T> SharedRwLock<T> {
         }
     }
 
+    /// 本句柄是否由 [`new`](Self::new) 初始化了锁，析构时负责销毁
+    pub(crate) fn is_owner(&self) -> bool {
+        self.owner
+    }
+
     fn as_raw(&self) -> *mut std::ffi::c_void {
         self.ptr as *mut std::ffi::c_void
     }
@@ -329,22 +502,16 @@ impl<T> SharedRwLock<T> {
     pub fn read(&self) -> Result<SharedRwLockReadGuard<'_, T>, RwLockError> {
         unsafe {
             match nix::libc::pthread_rwlock_rdlock(self.ptr) {
-                0 => Ok(SharedRwLockReadGuard {
-                    lock: &self.ptr,
-                    data: NonNull::new_unchecked(*self.data.get()),
-                }),
+                0 => Ok(SharedRwLockReadGuard::new(self)),
                 err_code => Err(RwLockError::ReadLockError(err_code)),
             }
         }
     }
 
-    fn try_read(&self) -> Result<SharedRwLockReadGuard<'_, T>, RwLockError> {
+    pub fn try_read(&self) -> Result<SharedRwLockReadGuard<'_, T>, RwLockError> {
         unsafe {
             match nix::libc::pthread_rwlock_tryrdlock(self.ptr) {
-                0 => Ok(SharedRwLockReadGuard {
-                    lock: &self.ptr,
-                    data: NonNull::new_unchecked(*self.data.get()),
-                }),
+                0 => Ok(SharedRwLockReadGuard::new(self)),
                 err_code => Err(RwLockError::TryReadLockError(err_code)),
             }
         }
@@ -353,21 +520,79 @@ impl<T> SharedRwLock<T> {
     pub fn write(&self) -> Result<SharedRwLockWriteGuard<'_, T>, RwLockError> {
         unsafe {
             match nix::libc::pthread_rwlock_wrlock(self.ptr) {
-                0 => Ok(SharedRwLockWriteGuard { lock: self }),
+                0 => Ok(SharedRwLockWriteGuard::new(self)),
                 err_code => Err(RwLockError::WriteLockError(err_code)),
             }
         }
     }
 
-    fn try_write(&self) -> Result<SharedRwLockWriteGuard<'_, T>, RwLockError> {
+    pub fn try_write(&self) -> Result<SharedRwLockWriteGuard<'_, T>, RwLockError> {
         unsafe {
             match nix::libc::pthread_rwlock_trywrlock(self.ptr) {
-                0 => Ok(SharedRwLockWriteGuard { lock: self }),
+                0 => Ok(SharedRwLockWriteGuard::new(self)),
                 err_code => Err(RwLockError::TryWriteLockError(err_code)),
             }
         }
     }
 
+    /// 获取读锁，对数据执行 f 后立即释放锁
+    ///
+    /// 锁只在 f 执行期间持有，适合从大块共享数据中取出少量字段，避免守护对象被长期持有。
+    pub fn read_map<R>(&self, f: impl FnOnce(&T) -> R) -> Result<R, RwLockError> {
+        let guard = self.read()?;
+        Ok(f(&guard))
+    }
+
+    /// 获取写锁，对数据执行 f 后立即释放锁
+    pub fn write_map<R>(&self, f: impl FnOnce(&mut T) -> R) -> Result<R, RwLockError> {
+        let mut guard = self.write()?;
+        Ok(f(&mut guard))
+    }
+
+    /// 获取写锁，在共享内存中原地修改数据后释放锁，返回 f 的结果
+    ///
+    /// 与 [`write_map`](Self::write_map) 等价，用于只修改大块负载中的部分字段：
+    /// 不需要 `T: Copy`/`Clone`，也不会像 `*guard = value` 那样整体替换。
+    pub fn update<R>(&self, f: impl FnOnce(&mut T) -> R) -> Result<R, RwLockError> {
+        self.write_map(f)
+    }
+
+    #[cfg(target_os = "linux")]
+    /// 在超时时间内获取读锁
+    pub fn timed_read(
+        &self,
+        timeout: Timeout,
+    ) -> Result<SharedRwLockReadGuard<'_, T>, RwLockError> {
+        let timespec = match timeout {
+            Timeout::Infinite => return self.read(),
+            Timeout::Val(dur) => deadline(dur),
+        };
+        unsafe {
+            match pthread_rwlock_timedrdlock(self.ptr, &timespec) {
+                0 => Ok(SharedRwLockReadGuard::new(self)),
+                err_code => Err(RwLockError::Timeout(err_code)),
+            }
+        }
+    }
+
+    #[cfg(target_os = "linux")]
+    /// 在超时时间内获取写锁
+    pub fn timed_write(
+        &self,
+        timeout: Timeout,
+    ) -> Result<SharedRwLockWriteGuard<'_, T>, RwLockError> {
+        let timespec = match timeout {
+            Timeout::Infinite => return self.write(),
+            Timeout::Val(dur) => deadline(dur),
+        };
+        unsafe {
+            match pthread_rwlock_timedwrlock(self.ptr, &timespec) {
+                0 => Ok(SharedRwLockWriteGuard::new(self)),
+                err_code => Err(RwLockError::Timeout(err_code)),
+            }
+        }
+    }
+
     fn unlock(&self) -> Result<(), RwLockError> {
         unsafe {
             match nix::libc::pthread_rwlock_unlock(self.ptr) {
@@ -382,3 +607,122 @@ impl<T> SharedRwLock<T> {
         unsafe { *self.data.get() }
     }
 }
+
+/// 计算从现在起经过 dur 后的绝对时间点（CLOCK_REALTIME），供 pthread 的 timed 系列函数使用
+fn deadline(dur: std::time::Duration) -> timespec {
+    let cur_time = std::time::SystemTime::now() + dur;
+    let since_epoch = cur_time.duration_since(std::time::UNIX_EPOCH).unwrap();
+    timespec {
+        tv_sec: since_epoch.as_secs() as _,
+        tv_nsec: since_epoch.subsec_nanos() as _,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::*;
+
+    // 进程内模拟共享内存，按 8 字节对齐
+    fn buffer() -> Vec<u64> {
+        vec![0u64; 64]
+    }
+
+    #[test]
+    fn timed_write_times_out_while_read_held() {
+        let mut mem = buffer();
+        let (lock, _) = SharedRwLock::new(mem.as_mut_ptr().cast(), 7u32).unwrap();
+
+        let guard = lock.read().unwrap();
+        assert!(matches!(lock.try_write(), Err(RwLockError::TryWriteLockError(_))));
+        let timeout = Timeout::Val(Duration::from_millis(20));
+        assert!(matches!(lock.timed_write(timeout), Err(RwLockError::Timeout(_))));
+        drop(guard);
+
+        let timeout = Timeout::Val(Duration::from_millis(20));
+        assert_eq!(*lock.timed_write(timeout).unwrap(), 7);
+    }
+
+    #[test]
+    fn timed_read_times_out_while_write_held() {
+        let mut mem = buffer();
+        let (lock, _) = SharedRwLock::new(mem.as_mut_ptr().cast(), 7u32).unwrap();
+        let addr = mem.as_mut_ptr() as usize;
+
+        let guard = lock.write().unwrap();
+        // 写锁持有者本线程再加读锁会返回 EDEADLK，因此在另一线程中尝试
+        std::thread::spawn(move || {
+            let (other, _) = SharedRwLock::<u32>::try_into(addr as *mut u8).unwrap();
+            assert!(matches!(other.try_read(), Err(RwLockError::TryReadLockError(_))));
+            let timeout = Timeout::Val(Duration::from_millis(20));
+            assert!(matches!(other.timed_read(timeout), Err(RwLockError::Timeout(_))));
+            // 挂载的句柄析构时不销毁锁，主线程的句柄仍可继续使用
+        })
+        .join()
+        .unwrap();
+        drop(guard);
+        assert_eq!(*lock.read().unwrap(), 7);
+    }
+
+    #[test]
+    fn read_map_extracts_field() {
+        #[derive(Clone, Copy)]
+        struct Scan {
+            seq: u32,
+            ranges: [f32; 8],
+        }
+
+        let mut mem = buffer();
+        let scan = Scan {
+            seq: 1,
+            ranges: [0.5; 8],
+        };
+        let (lock, _) = SharedRwLock::new(mem.as_mut_ptr().cast(), scan).unwrap();
+
+        lock.write_map(|scan| scan.seq += 1).unwrap();
+        assert_eq!(lock.read_map(|scan| scan.seq).unwrap(), 2);
+        assert_eq!(lock.read_map(|scan| scan.ranges.iter().sum::<f32>()).unwrap(), 4.0);
+        // 闭包返回后锁已释放
+        assert!(lock.try_write().is_ok());
+    }
+
+    #[test]
+    fn update_mutates_payload_in_place() {
+        // 不实现 Copy/Clone 的负载
+        struct Batch {
+            rows: u32,
+            columns: [u64; 4],
+        }
+
+        let mut mem = buffer();
+        let batch = Batch {
+            rows: 0,
+            columns: [1, 2, 3, 4],
+        };
+        let (lock, _) = SharedRwLock::new(mem.as_mut_ptr().cast(), batch).unwrap();
+
+        let old = lock
+            .update(|batch| {
+                batch.columns[2] = 30;
+                std::mem::replace(&mut batch.rows, 128)
+            })
+            .unwrap();
+        assert_eq!(old, 0);
+        assert_eq!(lock.read_map(|batch| batch.rows).unwrap(), 128);
+        assert_eq!(lock.read_map(|batch| batch.columns).unwrap(), [1, 2, 30, 4]);
+    }
+
+    #[test]
+    fn rwlock_is_shared_across_threads() {
+        let mut mem = buffer();
+        let (lock, _) = SharedRwLock::new(mem.as_mut_ptr().cast(), 0u32).unwrap();
+        let lock = std::sync::Arc::new(lock);
+
+        let writer = std::sync::Arc::clone(&lock);
+        std::thread::spawn(move || *writer.write().unwrap() = 42)
+            .join()
+            .unwrap();
+        assert_eq!(*lock.read().unwrap(), 42);
+    }
+}